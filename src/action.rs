@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Button, Gesture};
+
+/// What happens when a [`Trigger`] fires on the start screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    None,
+    DriveUp,
+    DriveDown,
+    GoToPosition1,
+    GoToPosition2,
+    OpenOptions,
+    Stop,
+    SavePosition1,
+    SavePosition2,
+    ToggleLock,
+    ToggleSitStand,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::None,
+        Action::DriveUp,
+        Action::DriveDown,
+        Action::GoToPosition1,
+        Action::GoToPosition2,
+        Action::ToggleSitStand,
+        Action::SavePosition1,
+        Action::SavePosition2,
+        Action::OpenOptions,
+        Action::ToggleLock,
+        Action::Stop,
+    ];
+
+    /// Actions that must stay bound to some trigger, otherwise the user could lock themselves
+    /// out.
+    const ESSENTIAL: [Action; 2] = [Action::OpenOptions, Action::ToggleLock];
+
+    /// Actions that only last while the buttons are held.
+    pub fn is_hold(self) -> bool {
+        matches!(self, Action::DriveUp | Action::DriveDown)
+    }
+
+    pub fn short_name(self) -> &'static str {
+        match self {
+            Action::None => "-",
+            Action::DriveUp => "up",
+            Action::DriveDown => "down",
+            Action::GoToPosition1 => "pos 1",
+            Action::GoToPosition2 => "pos 2",
+            Action::OpenOptions => "menu",
+            Action::Stop => "stop",
+            Action::SavePosition1 => "save 1",
+            Action::SavePosition2 => "save 2",
+            Action::ToggleLock => "lock",
+            Action::ToggleSitStand => "sit/stand",
+        }
+    }
+}
+
+impl core::fmt::Display for Action {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let string = match self {
+            Action::None => "Nothing",
+            Action::DriveUp => "Drive up",
+            Action::DriveDown => "Drive down",
+            Action::GoToPosition1 => "Go to position 1",
+            Action::GoToPosition2 => "Go to position 2",
+            Action::OpenOptions => "Open options",
+            Action::Stop => "Stop",
+            Action::SavePosition1 => "Save position 1",
+            Action::SavePosition2 => "Save position 2",
+            Action::ToggleLock => "Lock/unlock",
+            Action::ToggleSitStand => "Toggle sit/stand",
+        };
+
+        f.write_str(string)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GestureKind {
+    Press,
+    LongPress,
+    DoublePress,
+}
+
+impl GestureKind {
+    const ALL: [GestureKind; 3] = [
+        GestureKind::Press,
+        GestureKind::LongPress,
+        GestureKind::DoublePress,
+    ];
+}
+
+/// Buttons and chords that can be bound to actions.
+const BUTTONS: [Button; 6] = [
+    Button::Up,
+    Button::Down,
+    Button::Pos1,
+    Button::Pos2,
+    Button::UpAndDown,
+    Button::Pos1AndPos2,
+];
+
+pub const TRIGGER_COUNT: usize = BUTTONS.len() * GestureKind::ALL.len();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trigger {
+    pub buttons: Button,
+    pub gesture: GestureKind,
+}
+
+impl Trigger {
+    /// All triggers, grouped by buttons.
+    pub const ALL: [Trigger; TRIGGER_COUNT] = {
+        let gestures = GestureKind::ALL.len();
+        let mut all = [Trigger {
+            buttons: Button::empty(),
+            gesture: GestureKind::Press,
+        }; TRIGGER_COUNT];
+        let mut i = 0;
+        while i < TRIGGER_COUNT {
+            all[i] = Trigger {
+                buttons: BUTTONS[i / gestures],
+                gesture: GestureKind::ALL[i % gestures],
+            };
+            i += 1;
+        }
+        all
+    };
+
+    const fn index(self) -> Option<usize> {
+        let mut i = 0;
+        while i < TRIGGER_COUNT {
+            let trigger = Self::ALL[i];
+            if trigger.buttons.bits() == self.buttons.bits()
+                && trigger.gesture as u8 == self.gesture as u8
+            {
+                return Some(i);
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+impl core::fmt::Display for Trigger {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let buttons = match self.buttons {
+            Button::Up => "up",
+            Button::Down => "down",
+            Button::Pos1 => "pos1",
+            Button::Pos2 => "pos2",
+            Button::UpAndDown => "up+dn",
+            Button::Pos1AndPos2 => "p1+p2",
+            _ => "?",
+        };
+        let gesture = match self.gesture {
+            GestureKind::Press => "",
+            GestureKind::LongPress => " long",
+            GestureKind::DoublePress => " dbl",
+        };
+        write!(f, "{buttons}{gesture}")
+    }
+}
+
+/// Binds every [`Trigger`] to an [`Action`].
+///
+/// Press actions normally run as soon as the buttons are pressed. If the same buttons
+/// also have a long press action, they run on release instead to tell both apart.
+/// Hold actions, i.e. driving, only run while the buttons are still held.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionMap {
+    actions: [Action; TRIGGER_COUNT],
+}
+
+impl ActionMap {
+    pub const fn const_default() -> Self {
+        use GestureKind::{LongPress, Press};
+        Self {
+            actions: [Action::None; TRIGGER_COUNT],
+        }
+        .bind(Button::Up, Press, Action::DriveUp)
+        .bind(Button::Down, Press, Action::DriveDown)
+        .bind(Button::Pos1, Press, Action::GoToPosition1)
+        .bind(Button::Pos1, LongPress, Action::SavePosition1)
+        .bind(Button::Pos2, Press, Action::GoToPosition2)
+        .bind(Button::Pos2, LongPress, Action::SavePosition2)
+        .bind(Button::UpAndDown, Press, Action::OpenOptions)
+        .bind(Button::Pos1AndPos2, LongPress, Action::ToggleLock)
+    }
+
+    const fn bind(mut self, buttons: Button, gesture: GestureKind, action: Action) -> Self {
+        match (Trigger { buttons, gesture }).index() {
+            Some(index) => self.actions[index] = action,
+            None => panic!("default binding for a trigger that cannot be bound"),
+        }
+        self
+    }
+
+    pub fn get(&self, trigger: Trigger) -> Action {
+        trigger
+            .index()
+            .map_or(Action::None, |index| self.actions[index])
+    }
+
+    pub fn set(&mut self, trigger: Trigger, action: Action) -> Result<(), &'static str> {
+        let index = trigger.index().ok_or("trigger cannot be bound")?;
+
+        let previous = self.actions[index];
+        let removes_last_binding = previous != action
+            && Action::ESSENTIAL.contains(&previous)
+            && self.actions.iter().filter(|&&a| a == previous).count() == 1;
+        if removes_last_binding {
+            return Err("action must stay bound to a trigger");
+        }
+
+        self.actions[index] = action;
+        Ok(())
+    }
+
+    /// Determines the action to run for `gesture`, if any.
+    pub fn triggered_by(&self, gesture: Gesture) -> Option<Action> {
+        let action = |buttons, gesture| self.get(Trigger { buttons, gesture });
+        let has_long_press = |buttons| action(buttons, GestureKind::LongPress) != Action::None;
+
+        let action = match gesture {
+            Gesture::Pressed(buttons) => {
+                let press = action(buttons, GestureKind::Press);
+                (press.is_hold() || !has_long_press(buttons)).then_some(press)?
+            }
+            Gesture::ShortPress(buttons) => {
+                let press = action(buttons, GestureKind::Press);
+                (!press.is_hold() && has_long_press(buttons)).then_some(press)?
+            }
+            Gesture::LongPress(buttons) => action(buttons, GestureKind::LongPress),
+            Gesture::DoublePress(buttons) => {
+                Some(action(buttons, GestureKind::DoublePress)).filter(|a| !a.is_hold())?
+            }
+            Gesture::Repeat(_) | Gesture::Released(_) => return None,
+        };
+
+        (action != Action::None).then_some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(buttons: Button, gesture: GestureKind) -> Trigger {
+        Trigger { buttons, gesture }
+    }
+
+    #[test]
+    fn default_bindings() {
+        let map = ActionMap::const_default();
+        let bound = |buttons, gesture| map.get(trigger(buttons, gesture));
+        assert_eq!(bound(Button::Up, GestureKind::Press), Action::DriveUp);
+        assert_eq!(bound(Button::Down, GestureKind::Press), Action::DriveDown);
+        assert_eq!(
+            bound(Button::Pos1, GestureKind::Press),
+            Action::GoToPosition1
+        );
+        assert_eq!(
+            bound(Button::Pos1, GestureKind::LongPress),
+            Action::SavePosition1
+        );
+        assert_eq!(
+            bound(Button::Pos2, GestureKind::Press),
+            Action::GoToPosition2
+        );
+        assert_eq!(
+            bound(Button::Pos2, GestureKind::LongPress),
+            Action::SavePosition2
+        );
+        assert_eq!(
+            bound(Button::UpAndDown, GestureKind::Press),
+            Action::OpenOptions
+        );
+        assert_eq!(
+            bound(Button::Pos1AndPos2, GestureKind::LongPress),
+            Action::ToggleLock
+        );
+        assert_eq!(bound(Button::Up, GestureKind::DoublePress), Action::None);
+        // every essential action is reachable
+        for essential in Action::ESSENTIAL {
+            assert!(Trigger::ALL.iter().any(|&t| map.get(t) == essential));
+        }
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut map = ActionMap::const_default();
+        let double_up = trigger(Button::Up, GestureKind::DoublePress);
+        assert_eq!(map.set(double_up, Action::GoToPosition1), Ok(()));
+        assert_eq!(map.get(double_up), Action::GoToPosition1);
+
+        let up = trigger(Button::Up, GestureKind::Press);
+        assert_eq!(map.set(up, Action::None), Ok(()));
+        assert_eq!(map.get(up), Action::None);
+
+        let single_button = trigger(Button::Up | Button::Pos1, GestureKind::Press);
+        assert!(map.set(single_button, Action::Stop).is_err());
+        assert_eq!(map.get(single_button), Action::None);
+    }
+
+    #[test]
+    fn last_essential_binding_cannot_be_removed() {
+        let mut map = ActionMap::const_default();
+        let options = trigger(Button::UpAndDown, GestureKind::Press);
+        let lock = trigger(Button::Pos1AndPos2, GestureKind::LongPress);
+        assert!(map.set(options, Action::Stop).is_err());
+        assert!(map.set(lock, Action::None).is_err());
+        assert_eq!(map.get(options), Action::OpenOptions);
+        assert_eq!(map.get(lock), Action::ToggleLock);
+
+        // rebinding to the same action is no removal
+        assert_eq!(map.set(options, Action::OpenOptions), Ok(()));
+
+        // once bound elsewhere, the old trigger may change
+        let double_down = trigger(Button::Down, GestureKind::DoublePress);
+        assert_eq!(map.set(double_down, Action::OpenOptions), Ok(()));
+        assert_eq!(map.set(options, Action::Stop), Ok(()));
+        assert!(map.set(double_down, Action::None).is_err());
+    }
+
+    #[test]
+    fn press_runs_on_release_if_there_is_a_long_press() {
+        let map = ActionMap::const_default();
+        // pos1 has a long press binding, so its press action waits for the release
+        assert_eq!(map.triggered_by(Gesture::Pressed(Button::Pos1)), None);
+        assert_eq!(
+            map.triggered_by(Gesture::ShortPress(Button::Pos1)),
+            Some(Action::GoToPosition1)
+        );
+        assert_eq!(
+            map.triggered_by(Gesture::LongPress(Button::Pos1)),
+            Some(Action::SavePosition1)
+        );
+        // without a long press, the press action runs right away
+        assert_eq!(
+            map.triggered_by(Gesture::Pressed(Button::UpAndDown)),
+            Some(Action::OpenOptions)
+        );
+        assert_eq!(
+            map.triggered_by(Gesture::ShortPress(Button::UpAndDown)),
+            None
+        );
+    }
+
+    #[test]
+    fn hold_actions_run_while_pressed() {
+        let mut map = ActionMap::const_default();
+        let long_up = trigger(Button::Up, GestureKind::LongPress);
+        map.set(long_up, Action::GoToPosition2).unwrap();
+        assert_eq!(
+            map.triggered_by(Gesture::Pressed(Button::Up)),
+            Some(Action::DriveUp)
+        );
+        assert_eq!(map.triggered_by(Gesture::ShortPress(Button::Up)), None);
+
+        // holding is impossible after a double press
+        let double_down = trigger(Button::Down, GestureKind::DoublePress);
+        map.set(double_down, Action::DriveDown).unwrap();
+        assert_eq!(map.triggered_by(Gesture::DoublePress(Button::Down)), None);
+    }
+
+    #[test]
+    fn chord_triggers() {
+        let map = ActionMap::const_default();
+        assert_eq!(
+            map.triggered_by(Gesture::LongPress(Button::Pos1AndPos2)),
+            Some(Action::ToggleLock)
+        );
+        // the chord does not trigger the actions of its single buttons
+        assert_eq!(
+            map.triggered_by(Gesture::Pressed(Button::Pos1AndPos2)),
+            None
+        );
+        assert_eq!(
+            map.triggered_by(Gesture::ShortPress(Button::Pos1AndPos2)),
+            None
+        );
+        assert_eq!(map.triggered_by(Gesture::Repeat(Button::Pos1AndPos2)), None);
+        assert_eq!(map.triggered_by(Gesture::Released(Button::UpAndDown)), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Conversion of raw ADC1 readings to millivolts with the calibration data burnt into the
+//! eFuses of the ESP32.
+//!
+//! This follows the linear characterisation of ESP-IDF's `esp_adc_cal` for 11dB attenuation.
+
+/// Coefficient `a` is stored multiplied by this factor to keep precision in integer math.
+const LIN_COEFF_A_SCALE: u32 = 65536;
+const LIN_COEFF_A_ROUND: u32 = LIN_COEFF_A_SCALE / 2;
+const ADC_12_BIT_RES: u32 = 4096;
+
+const DEFAULT_VREF: u32 = 1100;
+
+const VREF_MASK: u32 = 0x1F;
+const VREF_STEP_SIZE: i32 = 7;
+const VREF_OFFSET: i32 = 1100;
+
+const TP_LOW1_OFFSET: i32 = 278;
+const TP_LOW_MASK: u32 = 0x7F;
+const TP_LOW_VOLTAGE: u32 = 150;
+const TP_HIGH1_OFFSET: i32 = 3265;
+const TP_HIGH_MASK: u32 = 0x1FF;
+const TP_HIGH_VOLTAGE: u32 = 850;
+const TP_STEP_SIZE: i32 = 4;
+
+// Scaling of the characteristic line for ADC1 at 11dB attenuation.
+const TP_ATTEN_SCALE: u32 = 224310;
+const TP_ATTEN_OFFSET: u32 = 54;
+const VREF_ATTEN_SCALE: u32 = 196602;
+const VREF_ATTEN_OFFSET: u32 = 142;
+
+/// `EFUSE_RD_BLK3_PART_RESERVE`, set if the two point values are burnt into BLK3.
+const BLK3_PART_RESERVE_BIT: u32 = 1 << 14;
+
+/// Raw eFuse register words that contain the ADC calibration data.
+#[derive(Debug, Clone, Copy)]
+pub struct EfuseWords {
+    pub blk0_rdata3: u32,
+    pub blk0_rdata4: u32,
+    pub blk3_rdata3: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    TwoPoint,
+    EfuseVref,
+    DefaultVref,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Characteristics {
+    coeff_a: u32,
+    coeff_b: u32,
+    source: Source,
+}
+
+impl Characteristics {
+    /// Picks the most accurate calibration data available: two point values, then the
+    /// measured reference voltage and finally the nominal reference voltage.
+    pub fn from_efuse(words: EfuseWords) -> Self {
+        if words.blk0_rdata3 & BLK3_PART_RESERVE_BIT != 0 {
+            let low = TP_LOW1_OFFSET
+                + TP_STEP_SIZE * decode_bits(words.blk3_rdata3 & TP_LOW_MASK, TP_LOW_MASK, true);
+            let high = TP_HIGH1_OFFSET
+                + TP_STEP_SIZE
+                    * decode_bits((words.blk3_rdata3 >> 7) & TP_HIGH_MASK, TP_HIGH_MASK, true);
+            return Self::from_two_point(low.unsigned_abs(), high.unsigned_abs());
+        }
+
+        let vref_bits = (words.blk0_rdata4 >> 8) & VREF_MASK;
+        if vref_bits != 0 {
+            let vref = VREF_OFFSET + VREF_STEP_SIZE * decode_bits(vref_bits, VREF_MASK, false);
+            return Self::from_vref(vref.unsigned_abs(), Source::EfuseVref);
+        }
+
+        Self::from_vref(DEFAULT_VREF, Source::DefaultVref)
+    }
+
+    /// Characteristic line through the readings `low` and `high` taken at 150mV and 850mV.
+    pub fn from_two_point(low: u32, high: u32) -> Self {
+        let delta_x = high.saturating_sub(low).max(1);
+        let delta_v = TP_HIGH_VOLTAGE - TP_LOW_VOLTAGE;
+        let coeff_a = (delta_v * TP_ATTEN_SCALE + delta_x / 2) / delta_x;
+        let coeff_b = (TP_HIGH_VOLTAGE + TP_ATTEN_OFFSET)
+            .saturating_sub((delta_v * high + delta_x / 2) / delta_x);
+        Self {
+            coeff_a,
+            coeff_b,
+            source: Source::TwoPoint,
+        }
+    }
+
+    /// Characteristic line for the reference voltage `vref` in millivolts.
+    pub fn from_vref(vref: u32, source: Source) -> Self {
+        Self {
+            coeff_a: vref * VREF_ATTEN_SCALE / ADC_12_BIT_RES,
+            coeff_b: VREF_ATTEN_OFFSET,
+            source,
+        }
+    }
+
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    pub fn millivolts(&self, raw: u16) -> u16 {
+        let scaled = (self.coeff_a * u32::from(raw) + LIN_COEFF_A_ROUND) / LIN_COEFF_A_SCALE;
+        (scaled + self.coeff_b).try_into().unwrap_or(u16::MAX)
+    }
+}
+
+/// Decodes `bits` as a signed value, the most significant bit of `mask` being the sign.
+fn decode_bits(bits: u32, mask: u32, is_twos_complement: bool) -> i32 {
+    let magnitude_mask = mask >> 1;
+    let is_negative = bits & mask & !magnitude_mask != 0;
+    if !is_negative {
+        return (bits & magnitude_mask) as i32;
+    }
+
+    let magnitude = if is_twos_complement {
+        (!bits).wrapping_add(1) & magnitude_mask
+    } else {
+        bits & magnitude_mask
+    };
+    -(magnitude as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected values are calculated with `esp_adc_cal_characterize` and
+    // `esp_adc_cal_raw_to_voltage` of ESP-IDF for ADC1 at 11dB attenuation.
+
+    #[test]
+    fn default_vref() {
+        let characteristics = Characteristics::from_efuse(EfuseWords {
+            blk0_rdata3: 0,
+            blk0_rdata4: 0,
+            blk3_rdata3: 0,
+        });
+        assert_eq!(characteristics.source(), Source::DefaultVref);
+        assert_eq!(characteristics.coeff_a, 52798);
+        assert_eq!(characteristics.coeff_b, 142);
+        assert_eq!(characteristics.millivolts(0), 142);
+        assert_eq!(characteristics.millivolts(2048), 1792);
+        assert_eq!(characteristics.millivolts(4095), 3441);
+    }
+
+    #[test]
+    fn efuse_vref() {
+        // +5 steps, i.e. 1135mV
+        let characteristics = Characteristics::from_efuse(EfuseWords {
+            blk0_rdata3: 0,
+            blk0_rdata4: 0b00101 << 8,
+            blk3_rdata3: 0,
+        });
+        assert_eq!(characteristics.source(), Source::EfuseVref);
+        assert_eq!(
+            characteristics,
+            Characteristics::from_vref(1135, Source::EfuseVref)
+        );
+        assert_eq!(characteristics.coeff_a, 54478);
+        assert_eq!(characteristics.millivolts(2048), 1844);
+
+        // sign and magnitude: -3 steps, i.e. 1079mV
+        let characteristics = Characteristics::from_efuse(EfuseWords {
+            blk0_rdata3: 0,
+            blk0_rdata4: 0b10011 << 8,
+            blk3_rdata3: 0,
+        });
+        assert_eq!(
+            characteristics,
+            Characteristics::from_vref(1079, Source::EfuseVref)
+        );
+    }
+
+    #[test]
+    fn two_point() {
+        let characteristics = Characteristics::from_two_point(278, 3265);
+        assert_eq!(characteristics.source(), Source::TwoPoint);
+        assert_eq!(characteristics.coeff_a, 52567);
+        assert_eq!(characteristics.coeff_b, 139);
+        assert_eq!(characteristics.millivolts(278), 362);
+        assert_eq!(characteristics.millivolts(3265), 2758);
+    }
+
+    #[test]
+    fn two_point_takes_precedence() {
+        // two's complement: low -2 steps (270), high +5 steps (3285)
+        let characteristics = Characteristics::from_efuse(EfuseWords {
+            blk0_rdata3: BLK3_PART_RESERVE_BIT,
+            blk0_rdata4: 0b00101 << 8,
+            blk3_rdata3: (5 << 7) | 0x7E,
+        });
+        assert_eq!(characteristics, Characteristics::from_two_point(270, 3285));
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(decode_bits(0x05, VREF_MASK, false), 5);
+        assert_eq!(decode_bits(0x15, VREF_MASK, false), -5);
+        assert_eq!(decode_bits(0x05, TP_LOW_MASK, true), 5);
+        assert_eq!(decode_bits(0x7B, TP_LOW_MASK, true), -5);
+        assert_eq!(decode_bits(0x1FF, TP_HIGH_MASK, true), -1);
+    }
+}
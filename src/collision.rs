@@ -0,0 +1,182 @@
+use embassy_time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{Direction, Millimeters},
+    speed::SpeedEstimator,
+};
+
+/// The controller ramps the motors up, so the speed is not checked right after starting.
+/// This is longer than the pulses of the soft approach, see [`crate::soft_approach`].
+const STARTUP_TIME: Duration = Duration::from_secs(1);
+/// Weight of a new measurement in the learned nominal speed.
+const LEARNING_RATE: f32 = 0.05;
+/// Slower speeds are not learned as the nominal speed, otherwise the collisions of a desk that
+/// is blocked right from its first run would never be detected.
+const MIN_NOMINAL_SPEED: f32 = 15.0;
+
+/// Stop if the desk moves slower than `percent` of its nominal speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sensitivity {
+    pub percent: u8,
+}
+
+impl Sensitivity {
+    /// Sensitivities offered in the options, `None` disables the collision detection.
+    pub const CHOICES: [Option<Sensitivity>; 4] = [
+        None,
+        Some(Sensitivity { percent: 40 }),
+        Some(Sensitivity { percent: 55 }),
+        Some(Sensitivity { percent: 70 }),
+    ];
+
+    fn fraction(self) -> f32 {
+        f32::from(self.percent) / 100.0
+    }
+}
+
+impl core::fmt::Display for Sensitivity {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "below {}% speed", self.percent)
+    }
+}
+
+/// Uninterrupted movement in one direction.
+#[derive(Debug, Clone)]
+struct Run {
+    direction: Direction,
+    started: Instant,
+    speed: SpeedEstimator,
+}
+
+/// Detects collisions by the desk slowing down while it moves.
+#[derive(Debug, Clone)]
+pub struct CollisionDetector {
+    /// Learned speed in mm/s for moving up and down.
+    nominal: [Option<f32>; 2],
+    run: Option<Run>,
+}
+
+impl CollisionDetector {
+    pub fn new() -> Self {
+        Self {
+            nominal: [None; 2],
+            run: None,
+        }
+    }
+
+    /// Feeds the direction the desk is moving in and its height, returns `true` if the desk
+    /// slowed down below `sensitivity` of the nominal speed.
+    pub fn update(
+        &mut self,
+        now: Instant,
+        moving: Option<Direction>,
+        height: Option<Millimeters>,
+        sensitivity: Option<Sensitivity>,
+    ) -> bool {
+        let (Some(direction), Some(height)) = (moving, height) else {
+            self.run = None;
+            return false;
+        };
+        let Some(index) = Self::index(direction) else {
+            self.run = None;
+            return false;
+        };
+
+        let run = match &mut self.run {
+            Some(run) if run.direction == direction => run,
+            run => run.insert(Run {
+                direction,
+                started: now,
+                speed: SpeedEstimator::new(),
+            }),
+        };
+        run.speed.push(now, height);
+
+        if now - run.started < STARTUP_TIME {
+            return false;
+        }
+        let Some(speed) = run.speed.speed() else {
+            return false;
+        };
+
+        let nominal = &mut self.nominal[index];
+        let Some(learned) = *nominal else {
+            if speed >= MIN_NOMINAL_SPEED {
+                *nominal = Some(speed);
+            } else {
+                log::debug!("desk moving {direction} at {speed:.0}mm/s, too slow to learn");
+            }
+            return false;
+        };
+        let slow = speed < learned * sensitivity.map_or(0.0, Sensitivity::fraction);
+        if slow {
+            log::warn!("desk moving {direction} at {speed:.0}mm/s, nominal is {learned:.0}mm/s");
+            self.run = None;
+        } else {
+            *nominal = Some(learned + (speed - learned) * LEARNING_RATE);
+        }
+        slow
+    }
+
+    fn index(direction: Direction) -> Option<usize> {
+        match direction {
+            Direction::Up => Some(0),
+            Direction::Down => Some(1),
+            Direction::Stopped | Direction::ResetDrive => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENSITIVITY: Option<Sensitivity> = Sensitivity::CHOICES[2];
+
+    /// Moves the desk up at `speed` mm/s from `start` ms until `end` ms, sampling every 100ms.
+    fn drive(detector: &mut CollisionDetector, start: u64, end: u64, speed: u64) -> bool {
+        (start..=end).step_by(100).any(|ms| {
+            let height = Millimeters::from_mm((700 + (ms - start) * speed / 1000) as u16);
+            detector.update(
+                Instant::from_millis(ms),
+                Some(Direction::Up),
+                Some(height),
+                SENSITIVITY,
+            )
+        })
+    }
+
+    #[test]
+    fn learns_and_detects_slowdown() {
+        let mut detector = CollisionDetector::new();
+        assert!(!drive(&mut detector, 0, 3000, 30));
+        assert!(detector.nominal[0].is_some());
+        detector.update(Instant::from_millis(3100), None, None, SENSITIVITY);
+
+        assert!(drive(&mut detector, 4000, 7000, 10));
+    }
+
+    #[test]
+    fn does_not_learn_implausible_speed() {
+        let mut detector = CollisionDetector::new();
+        assert!(!drive(&mut detector, 0, 3000, 5));
+        assert_eq!(detector.nominal[0], None);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut detector = CollisionDetector::new();
+        drive(&mut detector, 0, 3000, 30);
+        detector.update(Instant::from_millis(3100), None, None, None);
+        let blocked = (4000..=7000).step_by(100).any(|ms| {
+            detector.update(
+                Instant::from_millis(ms),
+                Some(Direction::Up),
+                Some(Millimeters::from_mm(800)),
+                None,
+            )
+        });
+        assert!(!blocked);
+    }
+}
@@ -4,56 +4,35 @@ use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use heapless::Vec;
 use serde::{Deserialize, Serialize};
 
-use crate::{gui::MainMenu, input::Inputs};
+use crate::{
+    gui::MainMenu, input::ButtonEvent, motion::DirectionControl, reminder::Reminder,
+    supervisor::Fault,
+};
 
 pub type Mutex<T> = embassy_sync::mutex::Mutex<CriticalSectionRawMutex, T>;
 pub type Signal<T> = embassy_sync::signal::Signal<CriticalSectionRawMutex, T>;
+pub type Channel<T, const N: usize> = embassy_sync::channel::Channel<CriticalSectionRawMutex, T, N>;
 
-pub static HEIGHT: Mutex<Millimeters> = Mutex::new(Millimeters(0));
+/// Current desk height, `None` while it is unknown, e.g. the ADC reading is implausible.
+pub static HEIGHT: Mutex<Option<Millimeters>> = Mutex::new(None);
+/// Signalled whenever a new height was published, even if it is unknown.
+pub static HEIGHT_MEASURED: Signal<()> = Signal::new();
 pub static RAW_HEIGHT: Signal<u16> = Signal::new();
-pub static INPUT: Mutex<Inputs> = Mutex::new(Inputs::new());
+pub static BUTTON_EVENTS: Channel<ButtonEvent, 16> = Channel::new();
 
 pub static GUI_MENU: Signal<MainMenu> = Signal::new();
+pub static REMINDER: Signal<Reminder> = Signal::new();
+pub static FAULTS: Channel<Fault, 4> = Channel::new();
 
 pub static DIRECTION: DirectionControl = DirectionControl::new();
 
-pub struct DirectionControl {
-    requested: Mutex<Direction>,
-    current: Mutex<Direction>,
-}
-
-impl DirectionControl {
-    pub const fn new() -> Self {
-        Self {
-            requested: Mutex::new(Direction::Stopped),
-            current: Mutex::new(Direction::Stopped),
-        }
-    }
-
-    pub async fn request(&self, new_direction: Direction) {
-        log::debug!("driving in direction {new_direction} requested.");
-        *self.requested.lock().await = new_direction;
-    }
-
-    pub async fn planned(&self) -> Option<Direction> {
-        let (cur, req) = {
-            let cur_guard = self.current.lock().await;
-            let req_guard = self.requested.lock().await;
-            (*cur_guard, *req_guard)
-        };
-
-        Some(req).filter(|&req| req != cur)
-    }
-
-    pub async fn acknowledge(&self, direction: Direction) {
-        *self.current.lock().await = direction;
-    }
-}
-
 pub static CALIBRATION: Signal<Calibration> = Signal::new();
 
 type Mapping = (u16, Millimeters);
 
+/// Readings may exceed the calibrated range by this much before they are rejected.
+const EXTRAPOLATION_MARGIN: u16 = 150;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Calibration {
     fix_points: Vec<Mapping, 20>,
@@ -96,6 +75,41 @@ impl Calibration {
     pub fn remove(&mut self, index: usize) {
         self.fix_points.remove(index);
     }
+
+    /// Replaces the point at `index`, keeping the points sorted by their ADC value.
+    ///
+    /// Fails if another point already uses `adc`, as that point would be lost otherwise.
+    pub fn replace(
+        &mut self,
+        index: usize,
+        adc: u16,
+        height: Millimeters,
+    ) -> Result<(), &'static str> {
+        let collides = self
+            .fix_points
+            .iter()
+            .enumerate()
+            .any(|(i, &(other, _))| i != index && other == adc);
+        if collides {
+            return Err("another calibration point uses this ADC value");
+        }
+
+        self.fix_points.remove(index);
+        self.insert(adc, height)
+    }
+
+    /// Checks whether `reading` lies within the calibrated range (plus a small margin),
+    /// i.e. whether [`Self::transform`] yields a trustworthy height for it.
+    pub fn is_within_range(&self, reading: u16) -> bool {
+        let [(lowest, _), .., (highest, _)] = self.fix_points[..] else {
+            return false;
+        };
+
+        let range = lowest.saturating_sub(EXTRAPOLATION_MARGIN)
+            ..=highest.saturating_add(EXTRAPOLATION_MARGIN);
+        range.contains(&reading)
+    }
+
     pub fn transform(&self, reading: u16) -> Millimeters {
         if self.fix_points.len() < 2 {
             return Millimeters::from_mm(0);
@@ -143,10 +157,6 @@ impl Millimeters {
         Self(value)
     }
 
-    pub const fn is_zero(self) -> bool {
-        self.0 == 0
-    }
-
     pub const fn cmp_fuzzy_eq(
         self,
         other: Self,
@@ -171,10 +181,6 @@ impl Millimeters {
         Self(self.0.saturating_sub(1))
     }
 
-    pub fn as_cm(self) -> u16 {
-        self.0 / 10
-    }
-
     pub fn as_mm(self) -> u16 {
         self.0
     }
@@ -188,6 +194,19 @@ pub enum Direction {
     ResetDrive,
 }
 
+impl Direction {
+    /// Whether driving in this direction brought the desk from `height` within `delta` of
+    /// `target` or past it.
+    pub fn reached(self, height: Millimeters, target: Millimeters, delta: Millimeters) -> bool {
+        let ordering = height.cmp_fuzzy_eq(target, delta);
+        match self {
+            Direction::Up => ordering.is_ge(),
+            Direction::Down => ordering.is_le(),
+            Direction::Stopped | Direction::ResetDrive => true,
+        }
+    }
+}
+
 impl core::fmt::Display for Direction {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.write_str(match self {
@@ -198,3 +217,61 @@ impl core::fmt::Display for Direction {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(points: &[(u16, u16)]) -> Calibration {
+        let mut calibration = Calibration::new();
+        for &(adc, height) in points {
+            calibration
+                .insert(adc, Millimeters::from_mm(height))
+                .unwrap();
+        }
+        calibration
+    }
+
+    #[test]
+    fn range_needs_two_points() {
+        assert!(!calibration(&[]).is_within_range(1000));
+        assert!(!calibration(&[(1000, 700)]).is_within_range(1000));
+
+        let calibration = calibration(&[(1000, 700), (3000, 1200)]);
+        assert!(calibration.is_within_range(1000 - EXTRAPOLATION_MARGIN));
+        assert!(calibration.is_within_range(3000 + EXTRAPOLATION_MARGIN));
+        assert!(!calibration.is_within_range(1000 - EXTRAPOLATION_MARGIN - 1));
+        assert!(!calibration.is_within_range(3000 + EXTRAPOLATION_MARGIN + 1));
+    }
+
+    #[test]
+    fn replace_keeps_points_sorted() {
+        let mut calibration = calibration(&[(1000, 700), (2000, 950), (3000, 1200)]);
+        calibration
+            .replace(0, 2500, Millimeters::from_mm(1100))
+            .unwrap();
+        let adcs: Vec<u16, 20> = calibration.iter().map(|&(adc, _)| adc).collect();
+        assert_eq!(adcs, [2000, 2500, 3000]);
+
+        // the point may keep its own ADC value
+        calibration
+            .replace(1, 2500, Millimeters::from_mm(1050))
+            .unwrap();
+        assert_eq!(calibration[1], (2500, Millimeters::from_mm(1050)));
+    }
+
+    #[test]
+    fn replace_rejects_colliding_adc() {
+        let mut calibration = calibration(&[(1000, 700), (3000, 1200)]);
+        assert!(calibration
+            .replace(0, 3000, Millimeters::from_mm(650))
+            .is_err());
+        assert_eq!(
+            &calibration[..],
+            [
+                (1000, Millimeters::from_mm(700)),
+                (3000, Millimeters::from_mm(1200))
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use embassy_time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Millimeters, Signal};
+
+/// Signalled on every button press to wake the display.
+pub static ACTIVITY: Signal<()> = Signal::new();
+/// Set while the display is off, so the button press that wakes it is ignored.
+static DISPLAY_OFF: AtomicBool = AtomicBool::new(false);
+
+/// Height changes below this are not considered movement of the desk.
+const MOVEMENT_THRESHOLD: Millimeters = Millimeters::from_mm(5);
+/// The content of the start screen moves by a pixel this often.
+const PIXEL_SHIFT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Offsets the start screen cycles through to spread the wear of the OLED.
+const PIXEL_SHIFT_OFFSETS: [(i8, i8); 9] = [
+    (0, 0),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Dim the display after `dim_minutes` and switch it off after `off_minutes` without activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayTimeout {
+    pub dim_minutes: u8,
+    pub off_minutes: u8,
+}
+
+impl DisplayTimeout {
+    /// Timeouts offered in the options, `None` keeps the display on.
+    pub const CHOICES: [Option<DisplayTimeout>; 5] = [
+        None,
+        Some(DisplayTimeout::new(1, 5)),
+        Some(DisplayTimeout::new(2, 10)),
+        Some(DisplayTimeout::new(5, 30)),
+        Some(DisplayTimeout::new(15, 60)),
+    ];
+
+    const fn new(dim_minutes: u8, off_minutes: u8) -> Self {
+        Self {
+            dim_minutes,
+            off_minutes,
+        }
+    }
+}
+
+impl core::fmt::Display for DisplayTimeout {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "dim {}m, off {}m", self.dim_minutes, self.off_minutes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    On,
+    Dimmed,
+    Off,
+}
+
+/// Tracks the activity to decide whether the display should be on, dimmed or off.
+#[derive(Debug, Clone)]
+pub struct DisplayPower {
+    last_activity: Instant,
+    /// Height at the last activity.
+    height: Option<Millimeters>,
+}
+
+impl DisplayPower {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_activity: now,
+            height: None,
+        }
+    }
+
+    pub fn activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Counts a movement of the desk as activity.
+    pub fn observe_height(&mut self, now: Instant, height: Option<Millimeters>) {
+        let Some(height) = height else {
+            return;
+        };
+        let moved = self.height.map_or(true, |last| {
+            last.cmp_fuzzy_eq(height, MOVEMENT_THRESHOLD).is_ne()
+        });
+        if moved {
+            self.height = Some(height);
+            self.activity(now);
+        }
+    }
+
+    pub fn state(&self, now: Instant, timeout: Option<DisplayTimeout>) -> PowerState {
+        let Some(timeout) = timeout else {
+            return PowerState::On;
+        };
+        let idle = now - self.last_activity;
+        if idle >= Duration::from_secs(u64::from(timeout.off_minutes) * 60) {
+            PowerState::Off
+        } else if idle >= Duration::from_secs(u64::from(timeout.dim_minutes) * 60) {
+            PowerState::Dimmed
+        } else {
+            PowerState::On
+        }
+    }
+}
+
+/// Offset of the start screen content at `now`.
+pub fn pixel_shift(now: Instant) -> (i8, i8) {
+    let step = now.as_secs() / PIXEL_SHIFT_INTERVAL.as_secs();
+    PIXEL_SHIFT_OFFSETS[(step % PIXEL_SHIFT_OFFSETS.len() as u64) as usize]
+}
+
+/// Whether a button press should only wake the display.
+pub fn is_display_off() -> bool {
+    DISPLAY_OFF.load(Ordering::Relaxed)
+}
+
+pub fn set_display_off(off: bool) {
+    DISPLAY_OFF.store(off, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Instant {
+        Instant::from_secs(secs)
+    }
+
+    const TIMEOUT: Option<DisplayTimeout> = Some(DisplayTimeout::new(1, 5));
+
+    fn mm(value: u16) -> Option<Millimeters> {
+        Some(Millimeters::from_mm(value))
+    }
+
+    #[test]
+    fn dims_and_switches_off_without_activity() {
+        let mut power = DisplayPower::new(at(0));
+        assert_eq!(power.state(at(59), TIMEOUT), PowerState::On);
+        assert_eq!(power.state(at(60), TIMEOUT), PowerState::Dimmed);
+        assert_eq!(power.state(at(299), TIMEOUT), PowerState::Dimmed);
+        assert_eq!(power.state(at(300), TIMEOUT), PowerState::Off);
+        assert_eq!(power.state(at(10_000), None), PowerState::On);
+
+        power.activity(at(300));
+        assert_eq!(power.state(at(300), TIMEOUT), PowerState::On);
+        assert_eq!(power.state(at(360), TIMEOUT), PowerState::Dimmed);
+    }
+
+    #[test]
+    fn movement_counts_as_activity() {
+        let mut power = DisplayPower::new(at(0));
+        power.observe_height(at(10), mm(700));
+        assert_eq!(power.state(at(69), TIMEOUT), PowerState::On);
+
+        // noise and unknown heights are no movement
+        power.observe_height(at(20), mm(704));
+        power.observe_height(at(30), None);
+        assert_eq!(power.state(at(70), TIMEOUT), PowerState::Dimmed);
+
+        power.observe_height(at(70), mm(706));
+        assert_eq!(power.state(at(129), TIMEOUT), PowerState::On);
+    }
+
+    #[test]
+    fn pixel_shift_cycles_through_all_offsets() {
+        let interval = PIXEL_SHIFT_INTERVAL.as_secs();
+        assert_eq!(pixel_shift(at(0)), (0, 0));
+        assert_eq!(pixel_shift(at(interval - 1)), (0, 0));
+        let cycle: [(i8, i8); 9] = core::array::from_fn(|i| pixel_shift(at(i as u64 * interval)));
+        assert_eq!(cycle, PIXEL_SHIFT_OFFSETS);
+        assert_eq!(pixel_shift(at(9 * interval)), (0, 0));
+
+        // every neighbouring offset is used once and consecutive offsets are a pixel apart
+        for (i, &(x, y)) in cycle.iter().enumerate() {
+            assert!(x.abs() <= 1 && y.abs() <= 1);
+            assert!(!cycle[..i].contains(&(x, y)));
+            let (next_x, next_y) = cycle[(i + 1) % cycle.len()];
+            assert!((next_x - x).abs() <= 1 && (next_y - y).abs() <= 1);
+        }
+    }
+}
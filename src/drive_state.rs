@@ -0,0 +1,322 @@
+use embassy_time::{Duration, Instant};
+
+use crate::data::Direction;
+
+/// Both outputs stay off for at least this long before the desk drives in another direction.
+pub const DEAD_TIME: Duration = Duration::from_millis(300);
+
+/// State of the motor outputs.
+///
+/// Does not do any I/O, the drive task switches the outputs according to
+/// [`DriveState::outputs`], which are marked as switched with
+/// [`DriveState::outputs_switched`] at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveState {
+    Idle,
+    /// Driving was requested but the outputs are not switched yet.
+    Starting(Direction),
+    Moving(Direction),
+    /// The outputs are off but the motor may still be running down.
+    Stopping {
+        from: Direction,
+        since: Instant,
+    },
+    /// The outputs are off until the dead time passed, then it continues with `to`.
+    Reversing {
+        from: Direction,
+        to: Direction,
+        since: Instant,
+    },
+    ResetDrive,
+    /// A task failed, all requests are rejected until the fault is cleared.
+    Fault,
+}
+
+impl DriveState {
+    /// Handles a request to drive in `direction`.
+    pub fn request(self, direction: Direction, now: Instant) -> Result<Self, &'static str> {
+        use Direction::{ResetDrive, Stopped};
+
+        let state = match (self, direction) {
+            (DriveState::Fault, Stopped) => DriveState::Fault,
+            (DriveState::Fault, _) => return Err("drive is in fault state"),
+
+            (DriveState::Idle, Stopped) => DriveState::Idle,
+            (DriveState::Idle, ResetDrive) => DriveState::ResetDrive,
+            (DriveState::Idle, direction) => DriveState::Starting(direction),
+
+            // the outputs were not switched yet, so no dead time is needed
+            (DriveState::Starting(_), Stopped) => DriveState::Idle,
+            (DriveState::Starting(_), ResetDrive) => return Err("reset drive requires idle"),
+            (DriveState::Starting(_), direction) => DriveState::Starting(direction),
+
+            (DriveState::Moving(from), Stopped) => DriveState::Stopping { from, since: now },
+            (DriveState::Moving(_), ResetDrive) => return Err("reset drive requires idle"),
+            (DriveState::Moving(from), to) if from == to => self,
+            (DriveState::Moving(from), to) => DriveState::Reversing {
+                from,
+                to,
+                since: now,
+            },
+
+            (DriveState::Stopping { .. }, Stopped) => self,
+            (DriveState::Stopping { .. }, ResetDrive) => return Err("reset drive requires idle"),
+            (DriveState::Stopping { from, .. }, to) if from == to => DriveState::Starting(to),
+            (DriveState::Stopping { from, since }, to) => DriveState::Reversing { from, to, since },
+
+            (DriveState::Reversing { from, since, .. }, Stopped) => {
+                DriveState::Stopping { from, since }
+            }
+            (DriveState::Reversing { .. }, ResetDrive) => return Err("reset drive requires idle"),
+            (DriveState::Reversing { from, .. }, to) if from == to => DriveState::Starting(to),
+            (DriveState::Reversing { from, since, .. }, to) => {
+                DriveState::Reversing { from, to, since }
+            }
+
+            (DriveState::ResetDrive, Stopped) => DriveState::Stopping {
+                from: ResetDrive,
+                since: now,
+            },
+            (DriveState::ResetDrive, ResetDrive) => DriveState::ResetDrive,
+            (DriveState::ResetDrive, _) => return Err("reset drive must be stopped first"),
+        };
+
+        Ok(state)
+    }
+
+    /// Finishes the dead time of stopping and reversing.
+    pub fn tick(self, now: Instant) -> Self {
+        match self {
+            DriveState::Stopping { since, .. } if now - since >= DEAD_TIME => DriveState::Idle,
+            DriveState::Reversing { to, since, .. } if now - since >= DEAD_TIME => {
+                DriveState::Starting(to)
+            }
+            _ => self,
+        }
+    }
+
+    /// Point in time at which [`Self::tick`] changes the state.
+    pub fn deadline(self) -> Option<Instant> {
+        match self {
+            DriveState::Stopping { since, .. } | DriveState::Reversing { since, .. } => {
+                Some(since + DEAD_TIME)
+            }
+            _ => None,
+        }
+    }
+
+    /// The outputs were switched to [`Self::outputs`].
+    pub fn outputs_switched(self) -> Self {
+        match self {
+            DriveState::Starting(direction) => DriveState::Moving(direction),
+            _ => self,
+        }
+    }
+
+    /// Stops immediately, without waiting for the dead time.
+    pub fn fault(self) -> Self {
+        DriveState::Fault
+    }
+
+    pub fn clear_fault(self, now: Instant) -> Self {
+        match self {
+            DriveState::Fault => DriveState::Stopping {
+                from: Direction::Stopped,
+                since: now,
+            },
+            _ => self,
+        }
+    }
+
+    /// How the outputs must be switched in this state.
+    pub fn outputs(self) -> Direction {
+        match self {
+            DriveState::Starting(direction) | DriveState::Moving(direction) => direction,
+            DriveState::ResetDrive => Direction::ResetDrive,
+            DriveState::Idle
+            | DriveState::Stopping { .. }
+            | DriveState::Reversing { .. }
+            | DriveState::Fault => Direction::Stopped,
+        }
+    }
+
+    /// Direction the desk is heading to, used by the GUI.
+    pub fn heading(self) -> Direction {
+        match self {
+            DriveState::Starting(direction)
+            | DriveState::Moving(direction)
+            | DriveState::Reversing { to: direction, .. } => direction,
+            DriveState::ResetDrive => Direction::ResetDrive,
+            DriveState::Idle | DriveState::Stopping { .. } | DriveState::Fault => {
+                Direction::Stopped
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use Direction::{Down, ResetDrive, Stopped, Up};
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn moving(direction: Direction) -> DriveState {
+        DriveState::Idle
+            .request(direction, at(0))
+            .unwrap()
+            .outputs_switched()
+    }
+
+    #[test]
+    fn idle() {
+        let idle = DriveState::Idle;
+        assert_eq!(idle.request(Stopped, at(0)), Ok(DriveState::Idle));
+        assert_eq!(idle.request(Up, at(0)), Ok(DriveState::Starting(Up)));
+        assert_eq!(idle.request(Down, at(0)), Ok(DriveState::Starting(Down)));
+        assert_eq!(idle.request(ResetDrive, at(0)), Ok(DriveState::ResetDrive));
+    }
+
+    #[test]
+    fn starting() {
+        let starting = DriveState::Starting(Up);
+        assert_eq!(starting.request(Stopped, at(0)), Ok(DriveState::Idle));
+        assert_eq!(starting.request(Up, at(0)), Ok(starting));
+        // nothing was switched yet, so no dead time
+        assert_eq!(
+            starting.request(Down, at(0)),
+            Ok(DriveState::Starting(Down))
+        );
+        assert!(starting.request(ResetDrive, at(0)).is_err());
+        assert_eq!(starting.outputs_switched(), DriveState::Moving(Up));
+    }
+
+    #[test]
+    fn moving_and_stopping() {
+        let state = moving(Up);
+        assert_eq!(state, DriveState::Moving(Up));
+        assert_eq!(state.request(Up, at(10)), Ok(state));
+        assert!(state.request(ResetDrive, at(10)).is_err());
+
+        let stopping = state.request(Stopped, at(10)).unwrap();
+        assert_eq!(
+            stopping,
+            DriveState::Stopping {
+                from: Up,
+                since: at(10)
+            }
+        );
+        assert_eq!(stopping.outputs(), Stopped);
+        assert_eq!(stopping.request(Stopped, at(20)), Ok(stopping));
+        assert!(stopping.request(ResetDrive, at(20)).is_err());
+        // continuing in the same direction needs no dead time
+        assert_eq!(stopping.request(Up, at(20)), Ok(DriveState::Starting(Up)));
+
+        assert_eq!(stopping.deadline(), Some(at(10) + DEAD_TIME));
+        assert_eq!(stopping.tick(at(309)), stopping);
+        assert_eq!(stopping.tick(at(310)), DriveState::Idle);
+    }
+
+    #[test]
+    fn reversing_waits_for_dead_time() {
+        let reversing = moving(Up).request(Down, at(100)).unwrap();
+        assert_eq!(
+            reversing,
+            DriveState::Reversing {
+                from: Up,
+                to: Down,
+                since: at(100)
+            }
+        );
+        assert_eq!(reversing.outputs(), Stopped);
+        assert_eq!(reversing.heading(), Down);
+        assert_eq!(reversing.deadline(), Some(at(400)));
+        assert_eq!(reversing.outputs_switched(), reversing);
+
+        assert_eq!(reversing.tick(at(399)), reversing);
+        let starting = reversing.tick(at(400));
+        assert_eq!(starting, DriveState::Starting(Down));
+        assert_eq!(starting.outputs(), Down);
+    }
+
+    #[test]
+    fn reversing_requests() {
+        let reversing = moving(Up).request(Down, at(100)).unwrap();
+        // back to the original direction right away, the outputs never changed
+        assert_eq!(reversing.request(Up, at(150)), Ok(DriveState::Starting(Up)));
+        assert_eq!(
+            reversing.request(Stopped, at(150)),
+            Ok(DriveState::Stopping {
+                from: Up,
+                since: at(100)
+            })
+        );
+        assert!(reversing.request(ResetDrive, at(150)).is_err());
+
+        // the dead time keeps running when stopping is turned into reversing
+        let stopping = moving(Down).request(Stopped, at(100)).unwrap();
+        let reversing = stopping.request(Up, at(250)).unwrap();
+        assert_eq!(reversing.deadline(), Some(at(400)));
+        assert_eq!(reversing.tick(at(400)), DriveState::Starting(Up));
+    }
+
+    #[test]
+    fn reset_drive() {
+        let reset = DriveState::ResetDrive;
+        assert_eq!(reset.outputs(), ResetDrive);
+        assert_eq!(reset.request(ResetDrive, at(0)), Ok(reset));
+        assert!(reset.request(Up, at(0)).is_err());
+        assert!(reset.request(Down, at(0)).is_err());
+
+        let stopping = reset.request(Stopped, at(50)).unwrap();
+        assert_eq!(
+            stopping,
+            DriveState::Stopping {
+                from: ResetDrive,
+                since: at(50)
+            }
+        );
+        assert_eq!(stopping.tick(at(350)), DriveState::Idle);
+    }
+
+    #[test]
+    fn fault() {
+        for state in [
+            DriveState::Idle,
+            DriveState::Starting(Up),
+            moving(Down),
+            moving(Up).request(Down, at(0)).unwrap(),
+            DriveState::ResetDrive,
+        ] {
+            assert_eq!(state.fault(), DriveState::Fault);
+        }
+
+        let fault = moving(Up).fault();
+        assert_eq!(fault.outputs(), Stopped);
+        assert_eq!(fault.heading(), Stopped);
+        assert_eq!(fault.deadline(), None);
+        assert_eq!(fault.tick(at(10_000)), fault);
+        assert_eq!(fault.request(Stopped, at(0)), Ok(fault));
+        assert!(fault.request(Up, at(0)).is_err());
+        assert!(fault.request(Down, at(0)).is_err());
+        assert!(fault.request(ResetDrive, at(0)).is_err());
+
+        // the dead time also applies after a fault
+        let cleared = fault.clear_fault(at(1000));
+        assert_eq!(
+            cleared,
+            DriveState::Stopping {
+                from: Stopped,
+                since: at(1000)
+            }
+        );
+        assert_eq!(cleared.tick(at(1299)), cleared);
+        assert_eq!(cleared.tick(at(1300)), DriveState::Idle);
+
+        assert_eq!(DriveState::Idle.clear_fault(at(0)), DriveState::Idle);
+        assert_eq!(moving(Up).clear_fault(at(0)), moving(Up));
+    }
+}
@@ -0,0 +1,268 @@
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_time::Instant;
+use heapless::{Deque, String};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    motion::AbortReason, storage::FlashRecord, string_format::format_truncated, supervisor::Task,
+};
+
+/// Blocking mutex so events can be recorded from synchronous code.
+static EVENT_LOG: Mutex<CriticalSectionRawMutex, RefCell<Option<EventLog>>> =
+    Mutex::new(RefCell::new(None));
+
+/// Sector after the statistics. The magic must be changed whenever the layout of
+/// [`EventLog`] changes.
+const RECORD: FlashRecord = FlashRecord::new("event log", 0xB000, [69, 86, 76, 3]);
+/// The oldest entries are dropped once the log is full.
+pub const CAPACITY: usize = 32;
+/// Panic messages are cut off after this many bytes.
+pub const MESSAGE_LEN: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoadError {
+    Read,
+    Deserialize,
+    MagicMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    Boot {
+        reset_reason: String<24>,
+    },
+    Crash {
+        reset_reason: String<24>,
+        /// Only known if the crash was a panic.
+        message: Option<String<MESSAGE_LEN>>,
+    },
+    ConfigurationLoadFailed(LoadError),
+    CalibrationChanged {
+        points: u8,
+    },
+    DriveAborted(AbortReason),
+    TaskFailed(Task),
+}
+
+impl Event {
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Event::Boot { .. } => "boot",
+            Event::Crash { .. } => "crash",
+            Event::ConfigurationLoadFailed(_) => "cfg load",
+            Event::CalibrationChanged { .. } => "calibration",
+            Event::DriveAborted(_) => "drive abort",
+            Event::TaskFailed(_) => "task failed",
+        }
+    }
+}
+
+impl core::fmt::Display for Event {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Event::Boot { reset_reason } => write!(f, "boot ({reset_reason})"),
+            Event::Crash {
+                reset_reason,
+                message: None,
+            } => write!(f, "crash ({reset_reason})"),
+            Event::Crash {
+                reset_reason,
+                message: Some(message),
+            } => write!(f, "crash ({reset_reason}): {message}"),
+            Event::ConfigurationLoadFailed(error) => write!(f, "config load failed ({error:?})"),
+            Event::CalibrationChanged { points } => write!(f, "calibration {points} points"),
+            Event::DriveAborted(reason) => write!(f, "drive aborted ({reason:?})"),
+            Event::TaskFailed(task) => write!(f, "{task:?} task failed"),
+        }
+    }
+}
+
+/// Panic message that is kept in memory which is not initialised on boot, so it survives
+/// the watchdog reset after the panic. The magic tells it apart from the random content
+/// after power-up.
+pub struct PanicMessage {
+    magic: u32,
+    length: u8,
+    bytes: [u8; MESSAGE_LEN],
+}
+
+impl PanicMessage {
+    const MAGIC: u32 = 0x5041_4e43;
+
+    pub const fn new() -> Self {
+        Self {
+            magic: 0,
+            length: 0,
+            bytes: [0; MESSAGE_LEN],
+        }
+    }
+
+    pub fn set(&mut self, message: core::fmt::Arguments) {
+        let message = format_truncated::<MESSAGE_LEN>(message);
+        self.bytes[..message.len()].copy_from_slice(message.as_bytes());
+        self.length = message.len() as u8;
+        self.magic = Self::MAGIC;
+    }
+
+    /// Returns the message of the last panic once.
+    pub fn take(&mut self) -> Option<String<MESSAGE_LEN>> {
+        if self.magic != Self::MAGIC {
+            return None;
+        }
+        self.magic = 0;
+        let bytes = self.bytes.get(..usize::from(self.length))?;
+        core::str::from_utf8(bytes).ok()?.try_into().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Number of the boot the event happened in.
+    pub boot: u16,
+    /// Time since that boot.
+    pub uptime_ms: u64,
+    pub event: Event,
+}
+
+impl core::fmt::Display for Entry {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "#{} {}.{:03}s {}",
+            self.boot,
+            self.uptime_ms / 1000,
+            self.uptime_ms % 1000,
+            self.event
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLog {
+    boot: u16,
+    entries: Deque<Entry, CAPACITY>,
+}
+
+impl EventLog {
+    const fn new() -> Self {
+        Self {
+            boot: 0,
+            entries: Deque::new(),
+        }
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    fn push(&mut self, event: Event) {
+        if let Event::Boot { .. } = event {
+            self.boot = self.boot.wrapping_add(1);
+        }
+        if self.entries.is_full() {
+            self.entries.pop_front();
+        }
+        let _ = self.entries.push_back(Entry {
+            boot: self.boot,
+            uptime_ms: Instant::now().as_millis(),
+            event,
+        });
+    }
+
+    /// Writes the log to the serial port.
+    pub fn dump(&self) {
+        log::info!("event log ({} entries):", self.entries.len());
+        for entry in self.entries() {
+            log::info!("{entry}");
+        }
+    }
+
+    fn load() -> Option<Self> {
+        RECORD
+            .load(&mut [0; FlashRecord::buffer_size::<EventLog>()])
+            .inspect_err(|_| log::warn!("starting a new event log"))
+            .ok()
+    }
+
+    fn store(&self) {
+        RECORD.store(self, &mut [0; FlashRecord::buffer_size::<EventLog>()]);
+    }
+}
+
+/// Appends `event` to the log and writes it to flash right away. Events are rare enough
+/// that this does not wear out the flash.
+pub fn record(event: Event) {
+    log::info!("recording event: {event}");
+    // Writing the flash takes milliseconds, so it is done on a copy outside of the critical
+    // section. Events are only recorded from the thread executor, so the copies cannot be
+    // written out of order.
+    let updated = EVENT_LOG.lock(|event_log| {
+        let Ok(mut event_log) = event_log.try_borrow_mut() else {
+            // the log is already borrowed further up the stack
+            return None;
+        };
+        let event_log =
+            event_log.get_or_insert_with(|| EventLog::load().unwrap_or_else(EventLog::new));
+        event_log.push(event);
+        Some(event_log.clone())
+    });
+    if let Some(event_log) = updated {
+        event_log.store();
+    }
+}
+
+/// Runs `f` with the current log.
+pub fn with_log<R>(f: impl FnOnce(&EventLog) -> R) -> Option<R> {
+    EVENT_LOG.lock(|event_log| {
+        let mut event_log = event_log.try_borrow_mut().ok()?;
+        let event_log =
+            event_log.get_or_insert_with(|| EventLog::load().unwrap_or_else(EventLog::new));
+        Some(f(event_log))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_message_is_taken_once() {
+        let mut message = PanicMessage::new();
+        assert_eq!(message.take(), None);
+
+        message.set(format_args!("panicked at {}:{}", "src/main.rs", 42));
+        assert_eq!(
+            message.take().as_deref(),
+            Some("panicked at src/main.rs:42")
+        );
+        assert_eq!(message.take(), None);
+    }
+
+    #[test]
+    fn long_panic_message_is_cut_off() {
+        let mut message = PanicMessage::new();
+        message.set(format_args!("{:x<1$}", "", MESSAGE_LEN + 10));
+        assert_eq!(message.take().map(|m| m.len()), Some(MESSAGE_LEN));
+    }
+
+    #[test]
+    fn random_memory_is_no_panic_message() {
+        let mut message = PanicMessage {
+            magic: 0x1234_5678,
+            length: 3,
+            bytes: [b'a'; MESSAGE_LEN],
+        };
+        assert_eq!(message.take(), None);
+
+        message.magic = PanicMessage::MAGIC;
+        message.length = u8::MAX;
+        assert_eq!(message.take(), None);
+    }
+
+    #[test]
+    fn event_log_fits_into_its_flash_sector() {
+        assert!(FlashRecord::buffer_size::<EventLog>() <= 0x1000);
+    }
+}
@@ -1,23 +1,64 @@
 use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
 
+mod button_mapping;
 mod calibration;
 mod calibration_point;
+mod choice;
+mod collision;
+mod display_settings;
+mod event_log;
+mod frame;
+mod long_press;
 mod options;
+mod reminder;
+mod reset_drive;
+mod soft_approach;
 mod start;
+mod statistics;
+mod unit;
 mod widgets;
 
-pub use calibration::{CalibrationMenu, CalibrationOptions, Selected};
+pub use button_mapping::{ActionSelection, BindingMenu, ButtonMapping};
+pub use calibration::{CalibrationMenu, CalibrationOptions, PointAction, PointOptions, Selected};
 pub use calibration_point::CalibrationPoint;
-pub use options::{OptionItem, Options, ResetDrive};
-pub use start::Start;
+pub use choice::{ChoiceSetting, Choices};
+pub use collision::{CollisionSettings, SensitivityItem};
+pub use display_settings::{
+    DisplaySetting, DisplaySettings, DisplaySettingsMenu, DisplayTimeoutSettings, TimeoutItem,
+};
+pub use event_log::{EventLogMenu, EventLogScreen};
+pub use frame::Frame;
+pub use long_press::LongPressSettings;
+pub use options::{OptionItem, Options};
+pub use reminder::{ReminderSettings, ScheduleItem};
+pub use reset_drive::{ResetDrive, ResetDriveResult};
+pub use soft_approach::{SoftApproachItem, SoftApproachSettings};
+pub use start::{PositionSaved, Progress, ReminderPrompt, Start};
+pub use statistics::StatisticsScreen;
+pub use unit::UnitSettings;
 pub use widgets::{Menu, MenuContent};
 
 pub enum MainMenu {
     Start(Start),
     Options(Options),
     ResetDrive(ResetDrive),
+    ResetDriveResult(ResetDriveResult),
     Calibration(CalibrationOptions),
     CalibrationPoint(CalibrationPoint),
+    PointOptions(PointOptions),
+    ButtonMapping(ButtonMapping),
+    ActionSelection(ActionSelection),
+    PositionSaved(PositionSaved),
+    ReminderPrompt(ReminderPrompt),
+    ReminderSettings(ReminderSettings),
+    SoftApproachSettings(SoftApproachSettings),
+    CollisionSettings(CollisionSettings),
+    UnitSettings(UnitSettings),
+    LongPressSettings(LongPressSettings),
+    DisplaySettings(DisplaySettings),
+    DisplayTimeoutSettings(DisplayTimeoutSettings),
+    Statistics(StatisticsScreen),
+    EventLog(EventLogScreen),
 }
 
 impl MainMenu {
@@ -29,8 +70,32 @@ impl MainMenu {
             MainMenu::Start(start) => start.display(display).await,
             MainMenu::Options(options) => options.display(display).await,
             MainMenu::ResetDrive(reset_drive) => reset_drive.display(display).await,
+            MainMenu::ResetDriveResult(result) => result.display(display).await,
             MainMenu::Calibration(calibration) => calibration.display(display).await,
             MainMenu::CalibrationPoint(point) => point.display(display).await,
+            MainMenu::PointOptions(options) => options.display(display).await,
+            MainMenu::ButtonMapping(mapping) => mapping.display(display).await,
+            MainMenu::ActionSelection(selection) => selection.display(display).await,
+            MainMenu::PositionSaved(saved) => saved.display(display).await,
+            MainMenu::ReminderPrompt(prompt) => prompt.display(display).await,
+            MainMenu::ReminderSettings(settings) => settings.display(display).await,
+            MainMenu::SoftApproachSettings(settings) => settings.display(display).await,
+            MainMenu::CollisionSettings(settings) => settings.display(display).await,
+            MainMenu::UnitSettings(settings) => settings.display(display).await,
+            MainMenu::LongPressSettings(settings) => settings.display(display).await,
+            MainMenu::DisplaySettings(settings) => settings.display(display).await,
+            MainMenu::DisplayTimeoutSettings(settings) => settings.display(display).await,
+            MainMenu::Statistics(statistics) => statistics.display(display).await,
+            MainMenu::EventLog(event_log) => event_log.display(display).await,
+        }
+    }
+
+    /// Whether this only refreshes the start screen shown by `previous`, e.g. with a new
+    /// height, which does not need the attention of the user.
+    pub fn is_refresh_of(&self, previous: &MainMenu) -> bool {
+        match (previous, self) {
+            (MainMenu::Start(previous), MainMenu::Start(start)) => previous.state == start.state,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,145 @@
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use crate::action::{Action, ActionMap, Trigger, TRIGGER_COUNT};
+
+use super::{
+    widgets::{footer, MenuContent},
+    MainMenu, Menu,
+};
+
+pub struct ButtonMapping {
+    pub menu: Menu<BindingMenu>,
+}
+
+impl ButtonMapping {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 exit | pos2 sel").await?;
+        Ok(())
+    }
+}
+
+impl From<ButtonMapping> for MainMenu {
+    fn from(value: ButtonMapping) -> Self {
+        Self::ButtonMapping(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BindingMenu {
+    actions: ActionMap,
+    selected: usize,
+}
+
+impl BindingMenu {
+    pub fn new(actions: ActionMap) -> Self {
+        Self {
+            actions,
+            selected: 0,
+        }
+    }
+
+    pub fn update_actions(&mut self, actions: &ActionMap) {
+        self.actions = actions.clone();
+    }
+
+    pub fn selected(&self) -> Trigger {
+        Trigger::ALL[self.selected]
+    }
+
+    pub fn action(&self) -> Action {
+        self.actions.get(self.selected())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    trigger: Trigger,
+    action: Action,
+}
+
+impl core::fmt::Display for Binding {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}: {}", self.trigger, self.action.short_name())
+    }
+}
+
+impl MenuContent for BindingMenu {
+    type Iter = core::array::IntoIter<Binding, TRIGGER_COUNT>;
+    type IterItem = Binding;
+
+    fn iter(&self) -> Self::Iter {
+        Trigger::ALL
+            .map(|trigger| Binding {
+                trigger,
+                action: self.actions.get(trigger),
+            })
+            .into_iter()
+    }
+
+    fn next(&mut self) {
+        self.selected = (self.selected + 1) % TRIGGER_COUNT;
+    }
+
+    fn prev(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(TRIGGER_COUNT - 1);
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        item.trigger == self.selected()
+    }
+}
+
+pub struct ActionSelection {
+    pub menu: Menu<Action>,
+}
+
+impl ActionSelection {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 back | pos2 set").await?;
+        Ok(())
+    }
+}
+
+impl From<ActionSelection> for MainMenu {
+    fn from(value: ActionSelection) -> Self {
+        Self::ActionSelection(value)
+    }
+}
+
+impl MenuContent for Action {
+    type Iter = core::array::IntoIter<Action, { Action::ALL.len() }>;
+    type IterItem = Action;
+
+    fn iter(&self) -> Self::Iter {
+        Action::ALL.into_iter()
+    }
+
+    fn next(&mut self) {
+        let index = action_index(*self);
+        *self = Action::ALL[(index + 1) % Action::ALL.len()];
+    }
+
+    fn prev(&mut self) {
+        let index = action_index(*self);
+        *self = Action::ALL[index.checked_sub(1).unwrap_or(Action::ALL.len() - 1)];
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        self == item
+    }
+}
+
+fn action_index(action: Action) -> usize {
+    Action::ALL
+        .iter()
+        .position(|&a| a == action)
+        .unwrap_or_default()
+}
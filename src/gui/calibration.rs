@@ -1,6 +1,9 @@
 use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
 
-use crate::data::{Calibration, Millimeters};
+use crate::{
+    data::{Calibration, Millimeters},
+    unit::Unit,
+};
 
 use super::{
     widgets::{footer, MenuContent},
@@ -16,12 +19,10 @@ impl CalibrationOptions {
     where
         D: DrawTarget<Color = BinaryColor> + Dimensions,
     {
-        self.menu
-            .display::<{ CalibrationMenu::MENU_STRING_LENGTH }>(display)
-            .await?;
+        self.menu.display(display).await?;
         let string = match self.menu.content.selected {
-            Selected::AddNew => "+- nav | pos1 exit | pos2 sel",
-            Selected::RemoveAll | Selected::ShowOne => "+- nav | pos1 exit | pos2 del",
+            Selected::AddNew | Selected::ShowOne => "+- nav | pos1 exit | pos2 sel",
+            Selected::RemoveAll => "+- nav | pos1 exit | pos2 del",
         };
         footer(display, string).await?;
         Ok(())
@@ -34,17 +35,93 @@ impl From<CalibrationOptions> for MainMenu {
     }
 }
 
+pub struct PointOptions {
+    pub menu: Menu<PointAction>,
+}
+
+impl PointOptions {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 exit | pos2 sel").await?;
+        Ok(())
+    }
+}
+
+impl From<PointOptions> for MainMenu {
+    fn from(value: PointOptions) -> Self {
+        Self::PointOptions(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointAction {
+    EditHeight,
+    RecaptureAdc,
+    Delete,
+}
+
+impl MenuContent for PointAction {
+    type Iter = core::array::IntoIter<PointAction, 3>;
+    type IterItem = PointAction;
+
+    fn iter(&self) -> Self::Iter {
+        [
+            PointAction::EditHeight,
+            PointAction::RecaptureAdc,
+            PointAction::Delete,
+        ]
+        .into_iter()
+    }
+
+    fn next(&mut self) {
+        *self = match self {
+            PointAction::EditHeight => PointAction::RecaptureAdc,
+            PointAction::RecaptureAdc => PointAction::Delete,
+            PointAction::Delete => PointAction::EditHeight,
+        }
+    }
+
+    fn prev(&mut self) {
+        *self = match self {
+            PointAction::EditHeight => PointAction::Delete,
+            PointAction::RecaptureAdc => PointAction::EditHeight,
+            PointAction::Delete => PointAction::RecaptureAdc,
+        }
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        self == item
+    }
+}
+
+impl core::fmt::Display for PointAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let string = match self {
+            PointAction::EditHeight => "Edit height",
+            PointAction::RecaptureAdc => "Re-measure ADC",
+            PointAction::Delete => "Delete point",
+        };
+
+        f.write_str(string)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CalibrationMenu {
     items: Calibration,
+    unit: Unit,
     shown_index: u8,
     selected: Selected,
 }
 
 impl CalibrationMenu {
-    pub fn new(items: Calibration) -> Self {
+    pub fn new(items: Calibration, unit: Unit) -> Self {
         Self {
             items,
+            unit,
             shown_index: 0,
             selected: Selected::AddNew,
         }
@@ -93,6 +170,7 @@ pub enum CalibrationItem {
         index: u8,
         adc: u16,
         height: Millimeters,
+        unit: Unit,
     },
 }
 
@@ -101,17 +179,18 @@ impl core::fmt::Display for CalibrationItem {
         match self {
             CalibrationItem::AddNew => f.write_str("Add new calibration point"),
             CalibrationItem::RemoveAll => f.write_str("Remove all calibration points"),
-            CalibrationItem::ShowOne { index, adc, height } => {
-                write!(f, "{index}) {adc} <=> {}mm", height.as_mm())
-            }
+            CalibrationItem::ShowOne {
+                index,
+                adc,
+                height,
+                unit,
+            } => write!(f, "{index}) {adc} <=> {}", unit.show_precise(*height)),
         }
     }
 }
 
 impl MenuContent for CalibrationMenu {
-    const MENU_STRING_LENGTH: usize = 120;
-
-    type Iter = <heapless::Vec<CalibrationItem, 3> as core::iter::IntoIterator>::IntoIter;
+    type Iter = <heapless::Vec<CalibrationItem, 22> as core::iter::IntoIterator>::IntoIter;
     type IterItem = CalibrationItem;
 
     fn iter(&self) -> Self::Iter {
@@ -121,17 +200,20 @@ impl MenuContent for CalibrationMenu {
 
             if !self.items.is_empty() {
                 items.push(CalibrationItem::RemoveAll)?;
-                let (adc, height) = self.items[usize::from(self.shown_index)];
-                items.push(CalibrationItem::ShowOne {
-                    index: self.shown_index,
-                    adc,
-                    height,
-                })?;
+                for (index, &(adc, height)) in (0..).zip(self.items.iter()) {
+                    items.push(CalibrationItem::ShowOne {
+                        index,
+                        adc,
+                        height,
+                        unit: self.unit,
+                    })?;
+                }
             }
             Ok::<_, CalibrationItem>(items.into_iter())
         };
 
-        inner().expect("push of at most 3 items into vec with capacity 3")
+        inner()
+            .expect("push of at most 2 items and 20 calibration points into vec with capacity 22")
     }
 
     fn next(&mut self) {
@@ -178,11 +260,13 @@ impl MenuContent for CalibrationMenu {
     }
 
     fn is_selected(&self, item: &Self::IterItem) -> bool {
-        matches!(
-            (item, &self.selected),
+        match (item, self.selected) {
             (CalibrationItem::AddNew, Selected::AddNew)
-                | (CalibrationItem::RemoveAll, Selected::RemoveAll)
-                | (CalibrationItem::ShowOne { .. }, Selected::ShowOne)
-        )
+            | (CalibrationItem::RemoveAll, Selected::RemoveAll) => true,
+            (CalibrationItem::ShowOne { index, .. }, Selected::ShowOne) => {
+                *index == self.shown_index
+            }
+            _ => false,
+        }
     }
 }
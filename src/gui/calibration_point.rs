@@ -9,13 +9,14 @@ use embedded_graphics::{
     text::{Alignment, Text},
 };
 
-use crate::{data::Millimeters, format};
+use crate::{data::Millimeters, format, unit::Unit};
 
 use super::{widgets::footer, MainMenu};
 
 pub struct CalibrationPoint {
     pub adc: u16,
     pub height: Millimeters,
+    pub unit: Unit,
 }
 
 impl CalibrationPoint {
@@ -27,9 +28,7 @@ impl CalibrationPoint {
             .font(&FONT_10X20)
             .text_color(BinaryColor::On)
             .build();
-        let cm = self.height.as_cm();
-        let mm = self.height.as_mm() % 10;
-        let string = format!(20, "{cm:>3},{mm}cm");
+        let string = format!(20, "{}", self.unit.show_precise(self.height));
         let text = Text::with_alignment(
             &string,
             display.bounding_box().anchor_point(AnchorPoint::Center),
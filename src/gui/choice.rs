@@ -0,0 +1,93 @@
+use core::fmt::Display;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use super::widgets::{footer, Menu, MenuContent};
+
+/// Screen to pick a setting out of `N` fixed choices.
+pub struct ChoiceSetting<T, const N: usize> {
+    pub menu: Menu<Choices<T, N>>,
+}
+
+impl<T: Copy + Display + PartialEq, const N: usize> ChoiceSetting<T, N> {
+    pub fn new(choices: Choices<T, N>) -> Self {
+        Self {
+            menu: Menu::new(choices),
+        }
+    }
+
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 back | pos2 set").await?;
+        Ok(())
+    }
+}
+
+/// The choices of a setting and the selected one.
+#[derive(Debug, Clone, Copy)]
+pub struct Choices<T, const N: usize> {
+    choices: [T; N],
+    selected: usize,
+}
+
+impl<T: Copy + PartialEq, const N: usize> Choices<T, N> {
+    /// Selects `current`, or the first choice if it is not one of `choices`.
+    pub fn new(choices: [T; N], current: T) -> Self {
+        let selected = choices
+            .iter()
+            .position(|&choice| choice == current)
+            .unwrap_or_default();
+        Self { choices, selected }
+    }
+
+    pub fn selected(&self) -> T {
+        self.choices[self.selected]
+    }
+}
+
+impl<T: Copy + Display + PartialEq, const N: usize> MenuContent for Choices<T, N> {
+    type Iter = core::array::IntoIter<T, N>;
+    type IterItem = T;
+
+    fn iter(&self) -> Self::Iter {
+        self.choices.into_iter()
+    }
+
+    fn next(&mut self) {
+        self.selected = (self.selected + 1) % N;
+    }
+
+    fn prev(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(N - 1);
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        self.selected() == *item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_wraps_around() {
+        let mut choices = Choices::new([10, 20, 30], 20);
+        choices.next();
+        assert_eq!(choices.selected(), 30);
+        choices.next();
+        assert_eq!(choices.selected(), 10);
+        choices.prev();
+        assert_eq!(choices.selected(), 30);
+        assert!(choices.is_selected(&30));
+        assert!(!choices.is_selected(&10));
+    }
+
+    #[test]
+    fn unknown_value_selects_the_first_choice() {
+        assert_eq!(Choices::new([10, 20, 30], 25).selected(), 10);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::collision::Sensitivity;
+
+use super::{choice::ChoiceSetting, MainMenu};
+
+pub type CollisionSettings = ChoiceSetting<SensitivityItem, { Sensitivity::CHOICES.len() }>;
+
+impl From<CollisionSettings> for MainMenu {
+    fn from(value: CollisionSettings) -> Self {
+        Self::CollisionSettings(value)
+    }
+}
+
+/// One of [`Sensitivity::CHOICES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensitivityItem(pub Option<Sensitivity>);
+
+impl core::fmt::Display for SensitivityItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.0 {
+            Some(sensitivity) => sensitivity.fmt(f),
+            None => f.write_str("Off"),
+        }
+    }
+}
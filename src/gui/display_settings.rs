@@ -0,0 +1,115 @@
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use crate::display_power::DisplayTimeout;
+
+use super::{
+    choice::ChoiceSetting,
+    widgets::{footer, Menu, MenuContent},
+    MainMenu,
+};
+
+pub struct DisplaySettings {
+    pub menu: Menu<DisplaySettingsMenu>,
+}
+
+impl DisplaySettings {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 back | pos2 change").await?;
+        Ok(())
+    }
+}
+
+impl From<DisplaySettings> for MainMenu {
+    fn from(value: DisplaySettings) -> Self {
+        Self::DisplaySettings(value)
+    }
+}
+
+pub type DisplayTimeoutSettings = ChoiceSetting<TimeoutItem, { DisplayTimeout::CHOICES.len() }>;
+
+impl From<DisplayTimeoutSettings> for MainMenu {
+    fn from(value: DisplayTimeoutSettings) -> Self {
+        Self::DisplayTimeoutSettings(value)
+    }
+}
+
+/// One of [`DisplayTimeout::CHOICES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutItem(pub Option<DisplayTimeout>);
+
+impl core::fmt::Display for TimeoutItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.0 {
+            Some(timeout) => timeout.fmt(f),
+            None => f.write_str("Always on"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySetting {
+    Timeout,
+    PixelShift,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisplaySettingsMenu {
+    pub timeout: Option<DisplayTimeout>,
+    pub pixel_shift: bool,
+    pub selected: DisplaySetting,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DisplaySettingsItem {
+    Timeout(Option<DisplayTimeout>),
+    PixelShift(bool),
+}
+
+impl MenuContent for DisplaySettingsMenu {
+    type Iter = core::array::IntoIter<DisplaySettingsItem, 2>;
+    type IterItem = DisplaySettingsItem;
+
+    fn iter(&self) -> Self::Iter {
+        [
+            DisplaySettingsItem::Timeout(self.timeout),
+            DisplaySettingsItem::PixelShift(self.pixel_shift),
+        ]
+        .into_iter()
+    }
+
+    fn next(&mut self) {
+        self.selected = match self.selected {
+            DisplaySetting::Timeout => DisplaySetting::PixelShift,
+            DisplaySetting::PixelShift => DisplaySetting::Timeout,
+        };
+    }
+
+    fn prev(&mut self) {
+        self.next();
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        matches!(
+            (item, self.selected),
+            (DisplaySettingsItem::Timeout(_), DisplaySetting::Timeout)
+                | (
+                    DisplaySettingsItem::PixelShift(_),
+                    DisplaySetting::PixelShift
+                )
+        )
+    }
+}
+
+impl core::fmt::Display for DisplaySettingsItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DisplaySettingsItem::Timeout(timeout) => TimeoutItem(*timeout).fmt(f),
+            DisplaySettingsItem::PixelShift(true) => f.write_str("Pixel shift: on"),
+            DisplaySettingsItem::PixelShift(false) => f.write_str("Pixel shift: off"),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use crate::event_log::{self, EventLog, CAPACITY};
+
+use super::{
+    widgets::{footer, Menu, MenuContent},
+    MainMenu,
+};
+
+pub struct EventLogScreen {
+    pub menu: Menu<EventLogMenu>,
+}
+
+impl EventLogScreen {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        self.menu.display(display).await?;
+        footer(display, "+- nav | pos1 back | pos2 export").await?;
+        Ok(())
+    }
+}
+
+impl From<EventLogScreen> for MainMenu {
+    fn from(value: EventLogScreen) -> Self {
+        Self::EventLog(value)
+    }
+}
+
+/// Entries of the event log, newest first. The details are only available in the serial
+/// export.
+#[derive(Debug, Clone, Copy)]
+pub struct EventLogMenu {
+    len: usize,
+    selected: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogLine {
+    index: usize,
+    boot: u16,
+    uptime_secs: u64,
+    event: &'static str,
+}
+
+impl EventLogMenu {
+    pub fn new(event_log: &EventLog) -> Self {
+        Self {
+            len: event_log.entries().count(),
+            selected: 0,
+        }
+    }
+}
+
+fn log_lines(event_log: &EventLog) -> [Option<LogLine>; CAPACITY] {
+    let mut lines = [None; CAPACITY];
+    let entries = event_log.entries().rev().enumerate();
+    for ((index, entry), line) in entries.zip(&mut lines) {
+        *line = Some(LogLine {
+            index,
+            boot: entry.boot,
+            uptime_secs: entry.uptime_ms / 1000,
+            event: entry.event.short_name(),
+        });
+    }
+    lines
+}
+
+impl MenuContent for EventLogMenu {
+    type Iter = core::iter::Flatten<core::array::IntoIter<Option<LogLine>, CAPACITY>>;
+    type IterItem = LogLine;
+
+    fn iter(&self) -> Self::Iter {
+        event_log::with_log(log_lines)
+            .unwrap_or([None; CAPACITY])
+            .into_iter()
+            .flatten()
+    }
+
+    fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.len.max(1);
+    }
+
+    fn prev(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.len.saturating_sub(1));
+    }
+
+    fn is_selected(&self, item: &Self::IterItem) -> bool {
+        item.index == self.selected
+    }
+}
+
+impl core::fmt::Display for LogLine {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "#{} {}s {}", self.boot, self.uptime_secs, self.event)
+    }
+}
@@ -0,0 +1,230 @@
+use core::convert::Infallible;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+
+/// Monochrome frame buffer the menus are rendered into, so only the pixels that changed
+/// since the last frame need to be sent to the display.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Eight horizontally adjacent pixels per byte, row by row.
+    pixels: [u8; WIDTH * HEIGHT / 8],
+}
+
+impl Frame {
+    pub const fn new() -> Self {
+        Self {
+            pixels: [0; WIDTH * HEIGHT / 8],
+        }
+    }
+
+    /// Pixels that differ from `previous`.
+    pub fn changes<'a>(
+        &'a self,
+        previous: &'a Frame,
+    ) -> impl Iterator<Item = Pixel<BinaryColor>> + 'a {
+        self.pixels
+            .iter()
+            .zip(previous.pixels.iter())
+            .enumerate()
+            .filter(|(_, (new, old))| new != old)
+            .flat_map(|(index, (&new, &old))| {
+                (0..8)
+                    .filter(move |bit| (new ^ old) & (1 << bit) != 0)
+                    .map(move |bit| {
+                        let position = index * 8 + bit;
+                        let point =
+                            Point::new((position % WIDTH) as i32, (position / WIDTH) as i32);
+                        Pixel(point, BinaryColor::from(new & (1 << bit) != 0))
+                    })
+            })
+    }
+}
+
+impl OriginDimensions for Frame {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl DrawTarget for Frame {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= HEIGHT {
+                continue;
+            }
+            let position = y * WIDTH + x;
+            let mask = 1 << (position % 8);
+            let byte = &mut self.pixels[position / 8];
+            if color.is_on() {
+                *byte |= mask;
+            } else {
+                *byte &= !mask;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.pixels.fill(if color.is_on() { u8::MAX } else { 0 });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use embedded_graphics::{
+        mono_font::ascii::{FONT_10X20, FONT_6X10},
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        data::Millimeters,
+        drive_state::DriveState,
+        gui::{MainMenu, Menu, OptionItem, Options, Progress, Start},
+        unit::Unit,
+    };
+
+    use super::*;
+
+    fn render(menu: MainMenu) -> Frame {
+        let mut frame = Frame::new();
+        block_on(menu.display(&mut frame)).unwrap();
+        frame
+    }
+
+    fn start(height: u16, progress: Option<Progress>) -> MainMenu {
+        Start {
+            height: Some(Millimeters::from_mm(height)),
+            state: DriveState::Idle,
+            locked: false,
+            unit: Unit::Centimeters,
+            progress,
+        }
+        .into()
+    }
+
+    fn progress(height: u16, speed: u16) -> MainMenu {
+        let progress = Progress {
+            start: Millimeters::from_mm(700),
+            target: Millimeters::from_mm(1100),
+            speed: Some(speed),
+        };
+        start(height, Some(progress))
+    }
+
+    fn options(selected: OptionItem) -> MainMenu {
+        Options {
+            menu: Menu::new(selected),
+        }
+        .into()
+    }
+
+    /// Size of a full flush in bytes.
+    const FULL: usize = WIDTH * HEIGHT / 8;
+
+    /// Buffer bytes of the display that changed and the bounding box of the changed pixels.
+    fn dirty(previous: &Frame, frame: &Frame) -> (usize, Rectangle) {
+        let mut bytes = [false; FULL];
+        let (mut min, mut max) = (Point::new(i32::MAX, i32::MAX), Point::zero());
+        let mut any = false;
+        for Pixel(point, _) in frame.changes(previous) {
+            any = true;
+            // the display stores eight vertically adjacent pixels per byte
+            bytes[point.y as usize / 8 * WIDTH + point.x as usize] = true;
+            min = min.component_min(point);
+            max = max.component_max(point);
+        }
+        let bytes = bytes.iter().filter(|&&changed| changed).count();
+        let area = if any {
+            Rectangle::with_corners(min, max)
+        } else {
+            Rectangle::zero()
+        };
+        assert!(bytes <= covered(area), "{bytes} bytes outside of {area:?}");
+        (bytes, area)
+    }
+
+    /// Buffer bytes of the display that contain pixels of `area`.
+    fn covered(area: Rectangle) -> usize {
+        let Some(bottom_right) = area.bottom_right() else {
+            return 0;
+        };
+        let pages = bottom_right.y as usize / 8 - area.top_left.y as usize / 8 + 1;
+        area.size.width as usize * pages
+    }
+
+    fn fits(area: Rectangle, size: Size) -> bool {
+        area.size.width <= size.width && area.size.height <= size.height
+    }
+
+    #[test]
+    fn unchanged_menu_has_no_changes() {
+        let frame = render(start(720, None));
+        assert_eq!(frame.changes(&render(start(720, None))).count(), 0);
+    }
+
+    #[test]
+    fn reset_drive_indicator() {
+        let MainMenu::Start(mut menu) = start(720, None) else {
+            unreachable!()
+        };
+        menu.state = DriveState::ResetDrive;
+        let (bytes, area) = dirty(&render(start(720, None)), &render(menu.into()));
+        assert!(bytes > 0);
+        assert_eq!(area.top_left.x, 0);
+    }
+
+    #[test]
+    fn start_sequence() {
+        let first = render(start(720, None));
+        let (full, _) = dirty(&Frame::new(), &first);
+
+        // 72cm -> 73cm, only the last digit changes
+        let (bytes, area) = dirty(&first, &render(start(725, None)));
+        assert!(fits(area, FONT_10X20.character_size), "{area:?}");
+        assert!(bytes * 4 < full);
+    }
+
+    #[test]
+    fn progress_sequence() {
+        let first = render(progress(800, 30));
+        dirty(&Frame::new(), &first);
+
+        // height, distance left and the progress bar change
+        let second = render(progress(810, 30));
+        let (bytes, _) = dirty(&first, &second);
+        assert!(bytes * 4 < FULL, "{bytes} bytes");
+
+        // only the speed changes
+        let (_, area) = dirty(&second, &render(progress(811, 31)));
+        assert!(fits(area, FONT_6X10.character_size), "{area:?}");
+    }
+
+    #[test]
+    fn menu_sequence() {
+        let first = render(options(OptionItem::SavePos1));
+
+        // the selection moves from the first to the second line
+        let (moved, moved_area) = dirty(&first, &render(options(OptionItem::SavePos2)));
+        assert_eq!(moved_area.top_left.y, 0);
+
+        // the selection stays in the middle line, so all lines scroll
+        let third = render(options(OptionItem::Calibration));
+        let (scrolled, scrolled_area) = dirty(&third, &render(options(OptionItem::ResetDrive)));
+        assert!(scrolled_area.size.height > 2 * moved_area.size.height);
+        assert!(moved < scrolled && scrolled < FULL);
+    }
+}
@@ -0,0 +1,11 @@
+use crate::input::LongPress;
+
+use super::{choice::ChoiceSetting, MainMenu};
+
+pub type LongPressSettings = ChoiceSetting<LongPress, { LongPress::CHOICES.len() }>;
+
+impl From<LongPressSettings> for MainMenu {
+    fn from(value: LongPressSettings) -> Self {
+        Self::LongPressSettings(value)
+    }
+}
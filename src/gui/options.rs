@@ -1,10 +1,4 @@
-use embedded_graphics::{
-    geometry::AnchorPoint,
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Alignment, Text},
-};
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
 
 use super::{
     widgets::{footer, Menu, MenuContent},
@@ -20,9 +14,7 @@ impl Options {
     where
         D: DrawTarget<Color = BinaryColor> + Dimensions,
     {
-        self.menu
-            .display::<{ OptionItem::MENU_STRING_LENGTH }>(display)
-            .await?;
+        self.menu.display(display).await?;
         let string = "+- nav | pos1 exit | pos2 sel";
         footer(display, string).await?;
         Ok(())
@@ -41,12 +33,20 @@ pub enum OptionItem {
     SavePos2,
     Calibration,
     ResetDrive,
+    ButtonMapping,
+    LongPress,
+    SoftApproach,
+    Collision,
+    Unit,
+    Display,
+    Reminders,
+    Statistics,
+    EventLog,
+    Lock,
 }
 
 impl MenuContent for OptionItem {
-    const MENU_STRING_LENGTH: usize = 83;
-
-    type Iter = core::array::IntoIter<OptionItem, 4>;
+    type Iter = core::array::IntoIter<OptionItem, 14>;
     type IterItem = OptionItem;
 
     fn iter(&self) -> Self::Iter {
@@ -55,6 +55,16 @@ impl MenuContent for OptionItem {
             OptionItem::SavePos2,
             OptionItem::Calibration,
             OptionItem::ResetDrive,
+            OptionItem::ButtonMapping,
+            OptionItem::LongPress,
+            OptionItem::SoftApproach,
+            OptionItem::Collision,
+            OptionItem::Unit,
+            OptionItem::Display,
+            OptionItem::Reminders,
+            OptionItem::Statistics,
+            OptionItem::EventLog,
+            OptionItem::Lock,
         ]
         .into_iter()
     }
@@ -64,16 +74,36 @@ impl MenuContent for OptionItem {
             OptionItem::SavePos1 => OptionItem::SavePos2,
             OptionItem::SavePos2 => OptionItem::Calibration,
             OptionItem::Calibration => OptionItem::ResetDrive,
-            OptionItem::ResetDrive => OptionItem::SavePos1,
+            OptionItem::ResetDrive => OptionItem::ButtonMapping,
+            OptionItem::ButtonMapping => OptionItem::LongPress,
+            OptionItem::LongPress => OptionItem::SoftApproach,
+            OptionItem::SoftApproach => OptionItem::Collision,
+            OptionItem::Collision => OptionItem::Unit,
+            OptionItem::Unit => OptionItem::Display,
+            OptionItem::Display => OptionItem::Reminders,
+            OptionItem::Reminders => OptionItem::Statistics,
+            OptionItem::Statistics => OptionItem::EventLog,
+            OptionItem::EventLog => OptionItem::Lock,
+            OptionItem::Lock => OptionItem::SavePos1,
         }
     }
 
     fn prev(&mut self) {
         *self = match self {
-            OptionItem::SavePos1 => OptionItem::ResetDrive,
+            OptionItem::SavePos1 => OptionItem::Lock,
             OptionItem::SavePos2 => OptionItem::SavePos1,
             OptionItem::Calibration => OptionItem::SavePos2,
             OptionItem::ResetDrive => OptionItem::Calibration,
+            OptionItem::ButtonMapping => OptionItem::ResetDrive,
+            OptionItem::LongPress => OptionItem::ButtonMapping,
+            OptionItem::SoftApproach => OptionItem::LongPress,
+            OptionItem::Collision => OptionItem::SoftApproach,
+            OptionItem::Unit => OptionItem::Collision,
+            OptionItem::Display => OptionItem::Unit,
+            OptionItem::Reminders => OptionItem::Display,
+            OptionItem::Statistics => OptionItem::Reminders,
+            OptionItem::EventLog => OptionItem::Statistics,
+            OptionItem::Lock => OptionItem::EventLog,
         }
     }
 
@@ -89,37 +119,18 @@ impl core::fmt::Display for OptionItem {
             OptionItem::SavePos2 => "Store position 2",
             OptionItem::Calibration => "Height calibration",
             OptionItem::ResetDrive => "Start reset drive",
+            OptionItem::ButtonMapping => "Button mapping",
+            OptionItem::LongPress => "Long press time",
+            OptionItem::SoftApproach => "Soft approach",
+            OptionItem::Collision => "Collision detection",
+            OptionItem::Unit => "Height unit",
+            OptionItem::Display => "Display power",
+            OptionItem::Reminders => "Sit/stand reminder",
+            OptionItem::Statistics => "Statistics",
+            OptionItem::EventLog => "Event log",
+            OptionItem::Lock => "Lock buttons",
         };
 
         f.write_str(string)
     }
 }
-
-pub struct ResetDrive;
-
-impl From<ResetDrive> for MainMenu {
-    fn from(value: ResetDrive) -> Self {
-        Self::ResetDrive(value)
-    }
-}
-
-impl ResetDrive {
-    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
-    where
-        D: DrawTarget<Color = BinaryColor> + Dimensions,
-    {
-        let text_style = MonoTextStyleBuilder::new()
-            .font(&FONT_6X10)
-            .text_color(BinaryColor::On)
-            .build();
-        let text = Text::with_alignment(
-            "Reset drive active.\nPress any button\nto stop/finish.",
-            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 6),
-            text_style,
-            Alignment::Left,
-        );
-
-        text.draw(display).map_err(|_| "failed to draw text")?;
-        Ok(())
-    }
-}
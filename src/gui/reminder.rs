@@ -0,0 +1,24 @@
+use crate::reminder::Schedule;
+
+use super::{choice::ChoiceSetting, MainMenu};
+
+pub type ReminderSettings = ChoiceSetting<ScheduleItem, { Schedule::CHOICES.len() }>;
+
+impl From<ReminderSettings> for MainMenu {
+    fn from(value: ReminderSettings) -> Self {
+        Self::ReminderSettings(value)
+    }
+}
+
+/// One of [`Schedule::CHOICES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleItem(pub Option<Schedule>);
+
+impl core::fmt::Display for ScheduleItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.0 {
+            Some(schedule) => schedule.fmt(f),
+            None => f.write_str("Off"),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use embassy_time::Duration;
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Text},
+};
+
+use crate::{
+    data::Millimeters,
+    format,
+    reset_drive::{Phase, TIMEOUT},
+    string_format::format_truncated,
+    unit::Unit,
+};
+
+use super::{
+    widgets::{footer, progress_bar},
+    MainMenu,
+};
+
+pub struct ResetDrive {
+    pub phase: Phase,
+    pub height: Option<Millimeters>,
+    pub elapsed: Duration,
+    pub unit: Unit,
+}
+
+impl From<ResetDrive> for MainMenu {
+    fn from(value: ResetDrive) -> Self {
+        Self::ResetDrive(value)
+    }
+}
+
+impl ResetDrive {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let height = match self.height {
+            Some(height) => format_truncated::<10>(format_args!("{}", self.unit.show(height))),
+            None => format_truncated(format_args!("???")),
+        };
+        let string = format!(
+            60,
+            "Reset drive {}s/{}s\n{}\nheight {}",
+            self.elapsed.as_secs(),
+            TIMEOUT.as_secs(),
+            self.phase,
+            height
+        );
+        let text = Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 6),
+            text_style,
+            Alignment::Left,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        let width = display.bounding_box().size.width;
+        let area = Rectangle::new(Point::new(0, 31), Size::new(width, 6));
+        let elapsed = self.elapsed.as_millis().try_into().unwrap_or(u32::MAX);
+        let total = TIMEOUT.as_millis().try_into().unwrap_or(u32::MAX);
+        progress_bar(display, area, elapsed, total).await?;
+
+        let text = Text::with_alignment(
+            "Keep the desk clear.",
+            Point::new(0, 48),
+            text_style,
+            Alignment::Left,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        footer(display, "any key: stop").await?;
+        Ok(())
+    }
+}
+
+pub struct ResetDriveResult {
+    pub phase: Phase,
+    /// Lowest height seen during the reset drive.
+    pub lowest: Option<Millimeters>,
+    /// Height of the lowest calibration point.
+    pub calibrated: Option<Millimeters>,
+    pub unit: Unit,
+}
+
+impl From<ResetDriveResult> for MainMenu {
+    fn from(value: ResetDriveResult) -> Self {
+        Self::ResetDriveResult(value)
+    }
+}
+
+impl ResetDriveResult {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let length = |height: Option<Millimeters>| match height {
+            Some(height) => {
+                format_truncated::<10>(format_args!("{}", self.unit.show_precise(height)))
+            }
+            None => format_truncated(format_args!("???")),
+        };
+        let (string, footer_string) = match self.phase {
+            Phase::Finished => (
+                format!(
+                    80,
+                    "Reset drive done.\nlowest     {:>9}\ncalibrated {:>9}",
+                    length(self.lowest),
+                    length(self.calibrated)
+                ),
+                "pos1 back | pos2 calibrate here",
+            ),
+            _ => (
+                format!(
+                    80,
+                    "Reset drive aborted.\nThe desk did not\nsettle in time."
+                ),
+                "any key: back",
+            ),
+        };
+        let text = Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 6),
+            text_style,
+            Alignment::Left,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        footer(display, footer_string).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use crate::gui::Frame;
+
+    use super::*;
+
+    #[test]
+    fn shows_any_height_in_every_unit() {
+        for unit in Unit::CHOICES {
+            for height in [None, Some(Millimeters::from_mm(u16::MAX))] {
+                let menu = ResetDrive {
+                    phase: Phase::Waiting,
+                    height,
+                    elapsed: TIMEOUT,
+                    unit,
+                };
+                block_on(menu.display(&mut Frame::new())).unwrap();
+
+                let menu = ResetDriveResult {
+                    phase: Phase::Finished,
+                    lowest: height,
+                    calibrated: height,
+                    unit,
+                };
+                block_on(menu.display(&mut Frame::new())).unwrap();
+            }
+        }
+    }
+}
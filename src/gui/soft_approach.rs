@@ -0,0 +1,24 @@
+use crate::soft_approach::SoftApproach;
+
+use super::{choice::ChoiceSetting, MainMenu};
+
+pub type SoftApproachSettings = ChoiceSetting<SoftApproachItem, { SoftApproach::CHOICES.len() }>;
+
+impl From<SoftApproachSettings> for MainMenu {
+    fn from(value: SoftApproachSettings) -> Self {
+        Self::SoftApproachSettings(value)
+    }
+}
+
+/// One of [`SoftApproach::CHOICES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftApproachItem(pub Option<SoftApproach>);
+
+impl core::fmt::Display for SoftApproachItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.0 {
+            Some(soft_approach) => soft_approach.fmt(f),
+            None => f.write_str("Off"),
+        }
+    }
+}
@@ -1,19 +1,43 @@
 use embedded_graphics::{
     geometry::AnchorPoint,
-    mono_font::{ascii::FONT_10X20, MonoTextStyleBuilder},
+    mono_font::{
+        ascii::{FONT_10X20, FONT_6X10},
+        MonoTextStyleBuilder,
+    },
     pixelcolor::BinaryColor,
     prelude::*,
     primitives::{PrimitiveStyle, Rectangle, StyledDrawable, Triangle},
     text::{Alignment, Text},
 };
 
-use crate::{data::Direction, data::Millimeters, format};
+use crate::{
+    data::Direction,
+    data::Millimeters,
+    drive_state::DriveState,
+    format,
+    reminder::{Posture, Reminder},
+    unit::Unit,
+};
 
-use super::MainMenu;
+use super::{
+    widgets::{footer, progress_bar},
+    MainMenu,
+};
 
 pub struct Start {
     pub height: Option<Millimeters>,
-    pub direction: Direction,
+    pub state: DriveState,
+    pub locked: bool,
+    pub unit: Unit,
+    /// Only set while driving to a position.
+    pub progress: Option<Progress>,
+}
+
+pub struct Progress {
+    pub start: Millimeters,
+    pub target: Millimeters,
+    /// Speed in mm/s.
+    pub speed: Option<u16>,
 }
 
 impl From<Start> for MainMenu {
@@ -33,8 +57,8 @@ impl Start {
             .build();
         let prim_style = PrimitiveStyle::with_fill(BinaryColor::On);
         let string = match self.height {
-            Some(height) => format!(10, "{:>3}cm", height.as_cm()),
-            None => format!(10, "???cm"),
+            Some(height) => format!(10, "{}", self.unit.show(height)),
+            None => format!(10, "???"),
         };
 
         let text = Text::with_alignment(
@@ -53,13 +77,192 @@ impl Start {
             Size::new_equal(text.bounding_box().size.height),
         );
 
-        match self.direction {
-            Direction::Up => triangle(rect, true).draw_styled(&prim_style, display),
-            Direction::Stopped => rect.draw_styled(&prim_style, display),
-            Direction::Down => triangle(rect, false).draw_styled(&prim_style, display),
-            Direction::ResetDrive => unimplemented!(),
+        match (self.state, self.state.heading()) {
+            (DriveState::Fault, _) => {
+                rect.draw_styled(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), display)
+            }
+            (_, Direction::Up) => triangle(rect, true).draw_styled(&prim_style, display),
+            (_, Direction::Stopped) => rect.draw_styled(&prim_style, display),
+            (_, Direction::Down) => triangle(rect, false).draw_styled(&prim_style, display),
+            (_, Direction::ResetDrive) => {
+                // the reset drive has no single direction, so both are shown
+                let half = Size::new(rect.size.width, rect.size.height / 2);
+                let lower = Rectangle::new(rect.top_left + half.y_axis(), half);
+                triangle(Rectangle::new(rect.top_left, half), true)
+                    .draw_styled(&prim_style, display)
+                    .and_then(|()| triangle(lower, false).draw_styled(&prim_style, display))
+            }
         }
         .map_err(|_| "failed to draw direction indicator")?;
+
+        if self.locked {
+            let top_right = display.bounding_box().anchor_point(AnchorPoint::TopRight);
+            lock_icon(display, top_right - Point::new(9, 0))
+                .map_err(|_| "failed to draw lock icon")?;
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.display(display, self.height, self.unit).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Progress {
+    async fn display<D>(
+        &self,
+        display: &mut D,
+        height: Option<Millimeters>,
+        unit: Unit,
+    ) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let remaining = height.map(|height| height.as_mm().abs_diff(self.target.as_mm()));
+
+        let string = match remaining {
+            Some(remaining) => format!(
+                24,
+                "->{} {} left",
+                unit.show(self.target),
+                unit.show(Millimeters::from_mm(remaining))
+            ),
+            None => format!(24, "->{}", unit.show(self.target)),
+        };
+        Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 8),
+            text_style,
+            Alignment::Left,
+        )
+        .draw(display)
+        .map_err(|_| "failed to draw text")?;
+
+        let distance = self.start.as_mm().abs_diff(self.target.as_mm());
+        let done = distance.saturating_sub(remaining.unwrap_or(distance));
+        let width = display.bounding_box().size.width;
+        let area = Rectangle::new(Point::new(0, 46), Size::new(width, 6));
+        progress_bar(display, area, done.into(), distance.into()).await?;
+
+        let string = match (self.speed, remaining) {
+            (Some(speed), Some(remaining)) if speed > 0 => {
+                format!(20, "{speed}mm/s ETA {}s", remaining.div_ceil(speed))
+            }
+            (Some(speed), _) => format!(20, "{speed}mm/s ETA --"),
+            (None, _) => format!(20, "--mm/s ETA --"),
+        };
+        Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::BottomLeft) - Point::new(0, 2),
+            text_style,
+            Alignment::Left,
+        )
+        .draw(display)
+        .map_err(|_| "failed to draw text")?;
+        Ok(())
+    }
+}
+
+/// Draws a padlock of 9x12 pixels.
+fn lock_icon<D>(display: &mut D, top_left: Point) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Rectangle::new(top_left + Point::new(2, 0), Size::new(5, 7))
+        .draw_styled(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), display)?;
+    Rectangle::new(top_left + Point::new(0, 5), Size::new(9, 7))
+        .draw_styled(&PrimitiveStyle::with_fill(BinaryColor::On), display)
+}
+
+pub struct PositionSaved {
+    pub position: u8,
+    pub height: Millimeters,
+    pub unit: Unit,
+}
+
+impl From<PositionSaved> for MainMenu {
+    fn from(value: PositionSaved) -> Self {
+        Self::PositionSaved(value)
+    }
+}
+
+impl PositionSaved {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let string = format!(
+            40,
+            "Position {} saved:\n{}",
+            self.position,
+            self.unit.show_precise(self.height)
+        );
+        let text = Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::Center),
+            text_style,
+            Alignment::Center,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        footer(display, "pos1 undo | other keys ok").await?;
+        Ok(())
+    }
+}
+
+pub struct ReminderPrompt {
+    pub reminder: Reminder,
+    /// Whether the desk can be moved to the suggested posture from the prompt.
+    pub can_move: bool,
+}
+
+impl From<ReminderPrompt> for MainMenu {
+    fn from(value: ReminderPrompt) -> Self {
+        Self::ReminderPrompt(value)
+    }
+}
+
+impl ReminderPrompt {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let request = match self.reminder.posture {
+            Posture::Sitting => "Time to sit down!",
+            Posture::Standing => "Time to stand up!",
+        };
+        let string = format!(
+            50,
+            "{request}\n{} for {}min",
+            self.reminder.posture.other(),
+            self.reminder.elapsed.as_secs() / 60
+        );
+        let text = Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::Center),
+            text_style,
+            Alignment::Center,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        let hint = if self.can_move {
+            "pos2 move | other keys ok"
+        } else {
+            "any key ok"
+        };
+        footer(display, hint).await?;
         Ok(())
     }
 }
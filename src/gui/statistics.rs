@@ -0,0 +1,56 @@
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+
+use crate::{format, statistics::Statistics};
+
+use super::{widgets::footer, MainMenu};
+
+pub struct StatisticsScreen {
+    pub statistics: Statistics,
+}
+
+impl From<StatisticsScreen> for MainMenu {
+    fn from(value: StatisticsScreen) -> Self {
+        Self::Statistics(value)
+    }
+}
+
+impl StatisticsScreen {
+    pub async fn display<D>(&self, display: &mut D) -> Result<(), &'static str>
+    where
+        D: DrawTarget<Color = BinaryColor> + Dimensions,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let s = &self.statistics;
+        let string = format!(
+            110,
+            "sitting   {:>5}h{:02}m\nstanding  {:>5}h{:02}m\nmoves {} ({} preset)\nmotor up   {:>6}s\nmotor down {:>6}s",
+            s.sitting_secs / 3600,
+            s.sitting_secs / 60 % 60,
+            s.standing_secs / 3600,
+            s.standing_secs / 60 % 60,
+            s.moves(),
+            s.preset_moves,
+            s.motor_up_ms / 1000,
+            s.motor_down_ms / 1000,
+        );
+        let text = Text::with_alignment(
+            &string,
+            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 6),
+            text_style,
+            Alignment::Left,
+        );
+        text.draw(display).map_err(|_| "failed to draw text")?;
+
+        footer(display, "pos1 back | pos2 log to serial").await?;
+        Ok(())
+    }
+}
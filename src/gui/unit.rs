@@ -0,0 +1,11 @@
+use crate::unit::Unit;
+
+use super::{choice::ChoiceSetting, MainMenu};
+
+pub type UnitSettings = ChoiceSetting<Unit, { Unit::CHOICES.len() }>;
+
+impl From<UnitSettings> for MainMenu {
+    fn from(value: UnitSettings) -> Self {
+        Self::UnitSettings(value)
+    }
+}
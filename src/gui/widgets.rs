@@ -1,5 +1,4 @@
 use core::fmt::Display;
-use core::fmt::Write;
 
 use embedded_graphics::{
     geometry::AnchorPoint,
@@ -9,10 +8,12 @@ use embedded_graphics::{
     },
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{Line, PrimitiveStyle, StyledDrawable},
-    text::{Alignment, Text},
+    primitives::{Line, PrimitiveStyle, Rectangle, StyledDrawable, Triangle},
+    text::{Alignment, Baseline, Text},
 };
-use heapless::String;
+use heapless::Deque;
+
+use crate::string_format::format_truncated;
 
 pub async fn footer<D>(display: &mut D, string: &str) -> Result<(), &'static str>
 where
@@ -44,6 +45,36 @@ where
     Ok(())
 }
 
+/// Draws an outline of `area` that is filled according to `done` out of `total`.
+pub async fn progress_bar<D>(
+    display: &mut D,
+    area: Rectangle,
+    done: u32,
+    total: u32,
+) -> Result<(), &'static str>
+where
+    D: DrawTarget<Color = BinaryColor> + Dimensions,
+{
+    area.draw_styled(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), display)
+        .map_err(|_| "failed to draw progress bar")?;
+
+    let done = done.min(total);
+    let width = (area.size.width * done).checked_div(total).unwrap_or(0);
+    Rectangle::new(area.top_left, Size::new(width, area.size.height))
+        .draw_styled(&PrimitiveStyle::with_fill(BinaryColor::On), display)
+        .map_err(|_| "failed to draw progress bar")?;
+    Ok(())
+}
+
+/// Number of menu lines that fit above the footer.
+const MENU_LINES: usize = 5;
+/// Height of a menu line in pixels.
+const LINE_HEIGHT: u32 = 10;
+/// Width kept free at the right edge for the scroll indicators.
+const SCROLL_INDICATOR_WIDTH: u32 = 6;
+/// Characters of an item that are rendered, more do not fit on the display anyway.
+const LINE_LENGTH: usize = 32;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Menu<T> {
     pub content: T,
@@ -53,7 +84,6 @@ pub trait MenuContent {
     fn iter(&self) -> Self::Iter;
     type IterItem: Display + Copy;
     type Iter: Iterator<Item = Self::IterItem>;
-    const MENU_STRING_LENGTH: usize;
     fn next(&mut self);
     fn prev(&mut self);
     fn is_selected(&self, item: &Self::IterItem) -> bool;
@@ -64,39 +94,165 @@ impl<T: MenuContent> Menu<T> {
         Self { content }
     }
 
-    pub async fn display<const MENU_STRING_LENGTH: usize>(
+    /// Draws the items that fit on the display as a list with the selected item inverted.
+    /// The list scrolls to keep the selected item in view, arrows at the right edge show
+    /// that there are more items above or below.
+    pub async fn display(
         &self,
-        display: &mut (impl DrawTarget<Color = BinaryColor> + Dimensions),
+        display: &mut impl DrawTarget<Color = BinaryColor>,
     ) -> Result<(), &'static str> {
-        let text_style = MonoTextStyleBuilder::new()
-            .font(&FONT_6X10)
-            .text_color(BinaryColor::On)
-            .build();
-
-        let build_str = || {
-            let mut string = String::<MENU_STRING_LENGTH>::new();
-            for item in self.content.iter() {
-                if self.content.is_selected(&item) {
-                    string.push_str("-> ")?;
-                } else {
-                    string.push_str("   ")?;
+        // Some contents build their items on the fly, so they are only iterated once. The
+        // shown items slide along until the selected one is in the middle.
+        let mut shown = Deque::<T::IterItem, MENU_LINES>::new();
+        let mut first_shown = 0;
+        let mut selected = None;
+        let mut count = 0;
+        for item in self.content.iter() {
+            if selected.is_none() && self.content.is_selected(&item) {
+                selected = Some(count);
+            }
+            count += 1;
+            if shown.is_full() {
+                if selected.is_some_and(|selected| first_shown + MENU_LINES / 2 >= selected) {
+                    continue;
                 }
-                writeln!(string, "{item}").map_err(|_| ())?;
+                shown.pop_front();
+                first_shown += 1;
             }
-            string.pop();
-            Ok(string)
-        };
-
-        let string = build_str().map_err(|_: ()| "failed to render menu string")?;
+            let _ = shown.push_back(item);
+        }
 
-        let text = Text::with_alignment(
-            &string,
-            display.bounding_box().anchor_point(AnchorPoint::TopLeft) + Point::new(0, 6),
-            text_style,
-            Alignment::Left,
-        );
+        let bounds = display.bounding_box();
+        let row_width = bounds.size.width.saturating_sub(SCROLL_INDICATOR_WIDTH);
+        let mut row_top = bounds.top_left;
+        for item in shown.iter().copied() {
+            let row = Rectangle::new(row_top, Size::new(row_width, LINE_HEIGHT));
+            draw_row(display, row, item, self.content.is_selected(&item))?;
+            row_top += Point::new(0, LINE_HEIGHT as i32);
+        }
 
-        text.draw(display).map_err(|_| "failed to draw text")?;
+        let indicator_x = bounds.top_left.x + row_width as i32 + 1;
+        if first_shown > 0 {
+            let base = Point::new(indicator_x, bounds.top_left.y + 3);
+            scroll_indicator(display, base, Point::new(2, -2))?;
+        }
+        if first_shown + shown.len() < count {
+            let base = Point::new(indicator_x, row_top.y - 4);
+            scroll_indicator(display, base, Point::new(2, 2))?;
+        }
         Ok(())
     }
 }
+
+fn draw_row<D>(
+    display: &mut D,
+    row: Rectangle,
+    item: impl Display,
+    selected: bool,
+) -> Result<(), &'static str>
+where
+    D: DrawTarget<Color = BinaryColor> + Dimensions,
+{
+    let text_color = if selected {
+        row.draw_styled(&PrimitiveStyle::with_fill(BinaryColor::On), display)
+            .map_err(|_| "failed to draw selection")?;
+        BinaryColor::Off
+    } else {
+        BinaryColor::On
+    };
+    let text_style = MonoTextStyleBuilder::new()
+        .font(&FONT_6X10)
+        .text_color(text_color)
+        .build();
+
+    // Items longer than a line are cut off at the edge of the display anyway.
+    let string = format_truncated::<LINE_LENGTH>(format_args!("{item}"));
+
+    Text::with_baseline(
+        &string,
+        row.top_left + Point::new(1, 0),
+        text_style,
+        Baseline::Top,
+    )
+    .draw(&mut display.clipped(&row))
+    .map_err(|_| "failed to draw text")?;
+    Ok(())
+}
+
+/// Draws an arrow head from the left end of `base` pointing towards `tip`.
+fn scroll_indicator<D>(display: &mut D, base: Point, tip: Point) -> Result<(), &'static str>
+where
+    D: DrawTarget<Color = BinaryColor> + Dimensions,
+{
+    Triangle::new(base, base + Point::new(4, 0), base + tip)
+        .draw_styled(&PrimitiveStyle::with_fill(BinaryColor::On), display)
+        .map_err(|_| "failed to draw scroll indicator")
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use crate::gui::Frame;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Numbers {
+        count: usize,
+        selected: usize,
+    }
+
+    impl MenuContent for Numbers {
+        type Iter = core::ops::Range<usize>;
+        type IterItem = usize;
+
+        fn iter(&self) -> Self::Iter {
+            0..self.count
+        }
+
+        fn next(&mut self) {
+            self.selected += 1;
+        }
+
+        fn prev(&mut self) {
+            self.selected -= 1;
+        }
+
+        fn is_selected(&self, item: &Self::IterItem) -> bool {
+            *item == self.selected
+        }
+    }
+
+    /// Line the selection is drawn in. Only the selection fills the leftmost column.
+    fn selected_line(count: usize, selected: usize) -> Option<i32> {
+        let blank = Frame::new();
+        let mut frame = Frame::new();
+        block_on(Menu::new(Numbers { count, selected }).display(&mut frame)).unwrap();
+        let mut lines = frame
+            .changes(&blank)
+            .filter(|Pixel(point, _)| point.x == 0)
+            .map(|Pixel(point, _)| point.y / LINE_HEIGHT as i32);
+        let line = lines.next();
+        assert!(lines.all(|other| Some(other) == line));
+        line
+    }
+
+    #[test]
+    fn scrolls_to_keep_the_selection_in_the_middle() {
+        assert_eq!(selected_line(14, 0), Some(0));
+        assert_eq!(selected_line(14, 1), Some(1));
+        assert_eq!(selected_line(14, 2), Some(2));
+        assert_eq!(selected_line(14, 3), Some(2));
+        assert_eq!(selected_line(14, 11), Some(2));
+        assert_eq!(selected_line(14, 12), Some(3));
+        assert_eq!(selected_line(14, 13), Some(4));
+    }
+
+    #[test]
+    fn short_menus_do_not_scroll() {
+        assert_eq!(selected_line(3, 0), Some(0));
+        assert_eq!(selected_line(3, 2), Some(2));
+        assert_eq!(selected_line(0, 0), None);
+    }
+}
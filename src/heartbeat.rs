@@ -0,0 +1,47 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant};
+
+pub static MEASURE: Heartbeat = Heartbeat::new("measure");
+/// Shared by all input tasks.
+pub static INPUT: Heartbeat = Heartbeat::new("input");
+pub static DRIVE: Heartbeat = Heartbeat::new("drive");
+
+/// A task is considered stalled if it did not beat for this long.
+const TIMEOUT: Duration = Duration::from_secs(1);
+/// Tasks that wait for external events must beat at least this often.
+pub const INTERVAL: Duration = Duration::from_millis(250);
+
+/// Proof of progress of a task, checked before feeding the watchdog.
+pub struct Heartbeat {
+    name: &'static str,
+    /// Milliseconds since boot of the last beat.
+    last_beat: AtomicU32,
+}
+
+impl Heartbeat {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            last_beat: AtomicU32::new(0),
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last_beat
+            .store(Instant::now().as_millis() as u32, Ordering::Relaxed);
+    }
+
+    fn is_alive(&self, now: Instant) -> bool {
+        let elapsed = (now.as_millis() as u32).wrapping_sub(self.last_beat.load(Ordering::Relaxed));
+        Duration::from_millis(elapsed.into()) <= TIMEOUT
+    }
+}
+
+/// Name of a task that stopped making progress, if any.
+pub fn stalled_task(now: Instant) -> Option<&'static str> {
+    [&MEASURE, &INPUT, &DRIVE]
+        .into_iter()
+        .find(|heartbeat| !heartbeat.is_alive(now))
+        .map(|heartbeat| heartbeat.name)
+}
@@ -1,10 +1,26 @@
 use bitflags::bitflags;
-use embassy_time::{Duration, Timer};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Instant, Timer};
 
-use crate::data::INPUT;
+use crate::data::BUTTON_EVENTS;
+
+use gesture::{GestureConfig, GestureDetector};
+
+mod gesture;
+
+pub use gesture::{Gesture, LongPress};
+
+/// Debounced state change of a single button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub button: Button,
+    pub pressed: bool,
+    /// When the change happened, events may queue up before they are handled.
+    pub time: Instant,
+}
 
 /// Describes the input button that was pressed.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone)]
 pub struct Inputs {
     pub up: State,
     pub down: State,
@@ -12,6 +28,7 @@ pub struct Inputs {
     pub pos1: State,
     /// Doubles as Ok or Store
     pub pos2: State,
+    gestures: GestureDetector,
 }
 
 impl Inputs {
@@ -21,8 +38,22 @@ impl Inputs {
             down: State::Inactive,
             pos1: State::Inactive,
             pos2: State::Inactive,
+            gestures: GestureDetector::new(GestureConfig::DEFAULT),
         }
     }
+
+    fn held(&self) -> Button {
+        [
+            (Button::Up, &self.up),
+            (Button::Down, &self.down),
+            (Button::Pos1, &self.pos1),
+            (Button::Pos2, &self.pos2),
+        ]
+        .into_iter()
+        .filter_map(|(button, state)| (*state == State::Active).then_some(button))
+        .collect()
+    }
+
     pub fn changed_since(&self, other: &Self) -> StateChanges {
         StateChanges {
             up: self.up.changed_since(&other.up),
@@ -32,52 +63,76 @@ impl Inputs {
         }
     }
 
-    async fn wait_for_change<F, T>(&mut self, mut check: F) -> T
-    where
-        F: FnMut(StateChanges) -> Option<T>,
-    {
-        loop {
-            let input = INPUT.lock().await.clone();
-            let changes = input.changed_since(self);
-            *self = input;
-            match check(changes) {
-                Some(res) => break res,
-                None => embassy_futures::yield_now().await,
+    /// Timings used to detect gestures from now on.
+    pub fn set_long_press(&mut self, long_press: LongPress) {
+        self.gestures.set_config(GestureConfig {
+            long_press: long_press.duration(),
+            ..GestureConfig::DEFAULT
+        });
+    }
+
+    fn apply(&mut self, event: ButtonEvent) {
+        let ButtonEvent {
+            button,
+            pressed,
+            time,
+        } = event;
+        let state = match button {
+            Button::Up => &mut self.up,
+            Button::Down => &mut self.down,
+            Button::Pos1 => &mut self.pos1,
+            Button::Pos2 => &mut self.pos2,
+            _ => {
+                log::warn!("ignoring event for unexpected button {button:?}");
+                return;
             }
+        };
+        if pressed {
+            state.press();
+        } else {
+            state.release();
         }
+        self.gestures.update(time, self.held());
     }
 
-    async fn is_unchanged_after<F, T>(
-        &self,
-        expected: &T,
-        wait_time: Duration,
-        mut check: F,
-    ) -> bool
+    async fn wait_for_change<F, T>(&mut self, mut check: F) -> T
     where
         F: FnMut(StateChanges) -> Option<T>,
-        T: PartialEq,
-        T: core::fmt::Debug,
     {
-        Timer::after(wait_time).await;
-        let input = INPUT.lock().await.clone();
-        let changes = input.changed_since(self);
-        let new = check(changes);
-
-        Some(expected) == new.as_ref()
+        let mut previous = self.clone();
+        let result = loop {
+            if let Some(res) = check(self.changed_since(&previous)) {
+                break res;
+            }
+            previous = self.clone();
+            // one event at a time, so a press and release that were queued together are not
+            // collapsed into no change at all
+            let event = BUTTON_EVENTS.receive().await;
+            self.apply(event);
+        };
+        // the events were consumed without looking at gestures
+        self.gestures.reset();
+        result
     }
 
-    pub async fn wait_for_press(&mut self) -> Button {
+    pub async fn wait_for_gesture(&mut self) -> Gesture {
         loop {
-            let button = self.wait_for_change(StateChanges::pressed).await;
-            log::trace!("registered button event: {button:?}");
-            let unchanged = self
-                .is_unchanged_after(&button, Duration::from_millis(80), StateChanges::pressed)
-                .await;
-            if unchanged {
-                log::debug!("detected button press: {button:?}");
-                break button;
-            } else {
-                log::trace!("button changed before timeout. Ignoring event.");
+            if let Some(gesture) = self.gestures.next_gesture() {
+                log::debug!("detected gesture: {gesture:?}");
+                break gesture;
+            }
+
+            match self.gestures.deadline() {
+                Some(deadline) => {
+                    match select(BUTTON_EVENTS.receive(), Timer::at(deadline)).await {
+                        Either::First(event) => self.apply(event),
+                        Either::Second(()) => self.gestures.update(deadline, self.held()),
+                    }
+                }
+                None => {
+                    let event = BUTTON_EVENTS.receive().await;
+                    self.apply(event);
+                }
             }
         }
     }
@@ -183,5 +238,6 @@ bitflags! {
         const Pos2 = 0b1000;
 
         const UpAndDown = Self::Up.bits() | Self::Down.bits();
+        const Pos1AndPos2 = Self::Pos1.bits() | Self::Pos2.bits();
     }
 }
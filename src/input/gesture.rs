@@ -0,0 +1,443 @@
+use embassy_time::{Duration, Instant};
+use heapless::Deque;
+use serde::{Deserialize, Serialize};
+
+use super::Button;
+
+/// Timings used to tell gestures apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureConfig {
+    /// Buttons pressed within this time are combined into a chord. The pressed buttons
+    /// must not change during this time for the press to be registered at all.
+    pub chord_delay: Duration,
+    /// Minimum time the buttons must be held for a long press.
+    pub long_press: Duration,
+    /// Maximum time between releasing and pressing the buttons again for a double press.
+    pub double_press_gap: Duration,
+    /// Interval of repeat events while the buttons are held after a long press.
+    pub repeat_interval: Duration,
+}
+
+impl GestureConfig {
+    pub const DEFAULT: Self = Self {
+        chord_delay: Duration::from_millis(80),
+        long_press: Duration::from_millis(1000),
+        double_press_gap: Duration::from_millis(400),
+        repeat_interval: Duration::from_millis(250),
+    };
+}
+
+/// Time the buttons must be held for a long press, configurable in the options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LongPress {
+    pub millis: u16,
+}
+
+impl LongPress {
+    pub const CHOICES: [LongPress; 4] = [
+        LongPress { millis: 500 },
+        LongPress { millis: 750 },
+        LongPress { millis: 1000 },
+        LongPress { millis: 1500 },
+    ];
+
+    pub const DEFAULT: Self = Self::CHOICES[2];
+
+    pub fn duration(self) -> Duration {
+        Duration::from_millis(self.millis.into())
+    }
+}
+
+impl core::fmt::Display for LongPress {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}.{:02}s", self.millis / 1000, self.millis % 1000 / 10)
+    }
+}
+
+/// Button gesture, the contained [`Button`] may hold multiple buttons for chords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// Buttons were pressed and did not change for [`GestureConfig::chord_delay`].
+    Pressed(Button),
+    /// Buttons were released before a long press was detected.
+    ShortPress(Button),
+    /// Buttons are held for [`GestureConfig::long_press`].
+    LongPress(Button),
+    /// Buttons are still held after a long press.
+    Repeat(Button),
+    /// Buttons were short pressed twice in a row. Follows the second [`Gesture::ShortPress`].
+    DoublePress(Button),
+    /// At least one of the buttons was released. Precedes [`Gesture::ShortPress`].
+    Released(Button),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    Settling {
+        since: Instant,
+    },
+    Held {
+        chord: Button,
+        next_event: Instant,
+        long: bool,
+        second_press: bool,
+    },
+    /// The chord ended but some of its buttons are still pressed.
+    WaitRelease,
+}
+
+/// Turns the states of the buttons into [`Gesture`]s.
+///
+/// Does not do any I/O, the caller passes in the pressed buttons and the current time.
+#[derive(Debug, Clone)]
+pub struct GestureDetector {
+    config: GestureConfig,
+    /// Time of the last update, the detector never goes back in time.
+    now: Instant,
+    held: Button,
+    phase: Phase,
+    last_short_press: Option<(Button, Instant)>,
+    pending: Deque<Gesture, 4>,
+}
+
+impl GestureDetector {
+    pub const fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            now: Instant::from_ticks(0),
+            held: Button::empty(),
+            phase: Phase::Idle,
+            last_short_press: None,
+            pending: Deque::new(),
+        }
+    }
+
+    /// Updates the detector with the currently pressed buttons.
+    ///
+    /// Must also be called once [`Self::deadline`] passed, even if the buttons did not change.
+    pub fn update(&mut self, now: Instant, held: Button) {
+        let now = now.max(self.now);
+        self.now = now;
+        // several deadlines may have passed if the update comes late
+        while self.deadline().is_some_and(|deadline| deadline <= now) {
+            self.advance_time(now);
+        }
+        if held != self.held {
+            self.held = held;
+            self.change_buttons(now);
+        }
+    }
+
+    /// Changes the timings, gestures that are already in progress keep their deadline.
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Point in time at which [`Self::update`] needs to be called again to detect time based
+    /// gestures.
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.phase {
+            Phase::Settling { since } => Some(since + self.config.chord_delay),
+            Phase::Held { next_event, .. } => Some(next_event),
+            Phase::Idle | Phase::WaitRelease => None,
+        }
+    }
+
+    pub fn next_gesture(&mut self) -> Option<Gesture> {
+        self.pending.pop_front()
+    }
+
+    /// Drops all gestures that were detected but not retrieved yet. Buttons that are still
+    /// held are ignored until they are released.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.last_short_press = None;
+        self.phase = if self.held.is_empty() {
+            Phase::Idle
+        } else {
+            Phase::WaitRelease
+        };
+    }
+
+    fn push(&mut self, gesture: Gesture) {
+        if self.pending.push_back(gesture).is_err() {
+            log::warn!("too many pending gestures, dropping {gesture:?}");
+        }
+    }
+
+    fn advance_time(&mut self, now: Instant) {
+        match self.phase {
+            Phase::Settling { since } if now >= since + self.config.chord_delay => {
+                let chord = self.held;
+                let second_press = self.last_short_press.is_some_and(|(button, released)| {
+                    button == chord && since - released <= self.config.double_press_gap
+                });
+                self.phase = Phase::Held {
+                    chord,
+                    next_event: since + self.config.chord_delay + self.config.long_press,
+                    long: false,
+                    second_press,
+                };
+                self.push(Gesture::Pressed(chord));
+            }
+            Phase::Held {
+                chord,
+                next_event,
+                long,
+                second_press,
+            } if now >= next_event => {
+                self.push(if long {
+                    Gesture::Repeat(chord)
+                } else {
+                    Gesture::LongPress(chord)
+                });
+                self.phase = Phase::Held {
+                    chord,
+                    next_event: now + self.config.repeat_interval,
+                    long: true,
+                    second_press,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn change_buttons(&mut self, now: Instant) {
+        let held = self.held;
+        match self.phase {
+            Phase::Idle | Phase::Settling { .. } if held.is_empty() => {
+                self.phase = Phase::Idle;
+            }
+            Phase::Idle | Phase::Settling { .. } => {
+                self.phase = Phase::Settling { since: now };
+            }
+            Phase::Held {
+                chord,
+                long,
+                second_press,
+                ..
+            } if !held.contains(chord) => {
+                self.push(Gesture::Released(chord));
+                if long {
+                    self.last_short_press = None;
+                } else {
+                    self.push(Gesture::ShortPress(chord));
+                    if second_press {
+                        self.push(Gesture::DoublePress(chord));
+                        self.last_short_press = None;
+                    } else {
+                        self.last_short_press = Some((chord, now));
+                    }
+                }
+                self.phase = if held.is_empty() {
+                    Phase::Idle
+                } else {
+                    Phase::WaitRelease
+                };
+            }
+            // additional buttons pressed while a chord is held are ignored
+            Phase::Held { .. } => {}
+            Phase::WaitRelease if held.is_empty() => {
+                self.phase = Phase::Idle;
+            }
+            Phase::WaitRelease => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn gestures(detector: &mut GestureDetector) -> heapless::Vec<Gesture, 8> {
+        core::iter::from_fn(|| detector.next_gesture()).collect()
+    }
+
+    #[test]
+    fn short_press() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        assert_eq!(gestures(&mut detector), []);
+        assert_eq!(detector.deadline(), Some(at(80)));
+
+        detector.update(at(80), Button::Up);
+        assert_eq!(gestures(&mut detector), [Gesture::Pressed(Button::Up)]);
+
+        detector.update(at(300), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Released(Button::Up),
+                Gesture::ShortPress(Button::Up)
+            ]
+        );
+        assert_eq!(detector.deadline(), None);
+    }
+
+    #[test]
+    fn bounce_is_ignored() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        detector.update(at(30), Button::empty());
+        assert_eq!(detector.deadline(), None);
+        detector.update(at(200), Button::empty());
+        assert_eq!(gestures(&mut detector), []);
+    }
+
+    #[test]
+    fn long_press_and_repeat() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Down);
+        detector.update(at(80), Button::Down);
+        assert_eq!(gestures(&mut detector), [Gesture::Pressed(Button::Down)]);
+        assert_eq!(detector.deadline(), Some(at(1080)));
+
+        detector.update(at(1080), Button::Down);
+        assert_eq!(gestures(&mut detector), [Gesture::LongPress(Button::Down)]);
+        assert_eq!(detector.deadline(), Some(at(1330)));
+
+        detector.update(at(1330), Button::Down);
+        assert_eq!(gestures(&mut detector), [Gesture::Repeat(Button::Down)]);
+
+        detector.update(at(1400), Button::empty());
+        assert_eq!(gestures(&mut detector), [Gesture::Released(Button::Down)]);
+    }
+
+    #[test]
+    fn double_press() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Pos1);
+        detector.update(at(80), Button::Pos1);
+        detector.update(at(200), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Pressed(Button::Pos1),
+                Gesture::Released(Button::Pos1),
+                Gesture::ShortPress(Button::Pos1),
+            ]
+        );
+
+        detector.update(at(400), Button::Pos1);
+        detector.update(at(480), Button::Pos1);
+        assert_eq!(gestures(&mut detector), [Gesture::Pressed(Button::Pos1)]);
+        detector.update(at(550), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Released(Button::Pos1),
+                Gesture::ShortPress(Button::Pos1),
+                Gesture::DoublePress(Button::Pos1),
+            ]
+        );
+    }
+
+    #[test]
+    fn slow_second_press_is_no_double_press() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Pos1);
+        detector.update(at(80), Button::Pos1);
+        detector.update(at(200), Button::empty());
+        gestures(&mut detector);
+
+        detector.update(at(700), Button::Pos1);
+        detector.update(at(780), Button::Pos1);
+        detector.update(at(850), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Pressed(Button::Pos1),
+                Gesture::Released(Button::Pos1),
+                Gesture::ShortPress(Button::Pos1),
+            ]
+        );
+    }
+
+    #[test]
+    fn chord() {
+        let both = Button::Up | Button::Down;
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        detector.update(at(40), both);
+        // the chord delay restarts with every change
+        assert_eq!(detector.deadline(), Some(at(120)));
+        detector.update(at(120), both);
+        assert_eq!(gestures(&mut detector), [Gesture::Pressed(both)]);
+
+        detector.update(at(200), Button::Up);
+        assert_eq!(
+            gestures(&mut detector),
+            [Gesture::Released(both), Gesture::ShortPress(both)]
+        );
+        // the remaining button does not start a new press until it is released
+        detector.update(at(400), Button::Up);
+        detector.update(at(500), Button::empty());
+        assert_eq!(gestures(&mut detector), []);
+    }
+
+    #[test]
+    fn late_update_uses_event_time() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        // the release is only handled after the long press deadline passed
+        detector.update(at(1500), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Pressed(Button::Up),
+                Gesture::LongPress(Button::Up),
+                Gesture::Released(Button::Up),
+            ]
+        );
+    }
+
+    #[test]
+    fn time_does_not_go_back() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        detector.update(at(80), Button::Up);
+        detector.update(at(70), Button::empty());
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Pressed(Button::Up),
+                Gesture::Released(Button::Up),
+                Gesture::ShortPress(Button::Up),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_long_press() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.set_config(GestureConfig {
+            long_press: LongPress::CHOICES[0].duration(),
+            ..GestureConfig::DEFAULT
+        });
+        detector.update(at(0), Button::Pos2);
+        detector.update(at(580), Button::Pos2);
+        assert_eq!(
+            gestures(&mut detector),
+            [
+                Gesture::Pressed(Button::Pos2),
+                Gesture::LongPress(Button::Pos2),
+            ]
+        );
+    }
+
+    #[test]
+    fn reset_ignores_held_buttons() {
+        let mut detector = GestureDetector::new(GestureConfig::DEFAULT);
+        detector.update(at(0), Button::Up);
+        detector.update(at(80), Button::Up);
+        detector.reset();
+        assert_eq!(detector.deadline(), None);
+        detector.update(at(2000), Button::empty());
+        assert_eq!(gestures(&mut detector), []);
+    }
+}
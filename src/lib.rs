@@ -0,0 +1,29 @@
+//! Everything of the firmware that does not touch the hardware directly, so it can be tested
+//! on the host with `cargo +stable test --lib --target <host triple>`.
+
+#![cfg_attr(not(test), no_std)]
+// Most types are created in `const` contexts for statics, where `Default` cannot be used.
+#![allow(clippy::new_without_default)]
+
+pub mod action;
+pub mod adc;
+pub mod collision;
+pub mod data;
+pub mod display_power;
+pub mod drive_state;
+pub mod event_log;
+pub mod gui;
+pub mod heartbeat;
+pub mod input;
+pub mod motion;
+pub mod operation_mode;
+pub mod reminder;
+pub mod reset_drive;
+pub mod sampling;
+pub mod soft_approach;
+pub mod speed;
+pub mod statistics;
+pub mod storage;
+pub mod string_format;
+pub mod supervisor;
+pub mod unit;
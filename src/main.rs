@@ -2,60 +2,135 @@
 #![no_main]
 #![feature(type_alias_impl_trait)]
 
-use debouncr::DebouncerStateful;
-use embassy_time::{Duration, Instant, Ticker, Timer};
-use embedded_graphics::{draw_target::DrawTarget, pixelcolor::BinaryColor};
+use embassy_futures::select::{select3, Either3};
+use embassy_time::{with_timeout, Duration, Instant, Ticker, Timer};
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+};
 use esp_backtrace as _;
 use esp_println::logger::init_logger;
 use hal::{
     analog::adc::{Adc, AdcConfig, AdcPin, Attenuation},
     clock::ClockControl,
+    get_core,
     gpio::{Gpio34, Io, Level, Pull},
     i2c::I2C,
-    peripherals::{Peripherals, ADC1},
+    macros::ram,
+    peripherals::{Peripherals, ADC1, EFUSE},
     prelude::*,
+    reset::get_reset_reason,
+    rtc_cntl::{Rtc, SocResetReason},
     system::SystemControl,
     timer::timg::TimerGroup,
 };
 use heapless::String;
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-
-mod data;
-mod gui;
-mod input;
-mod operation_mode;
-mod storage;
-mod string_format;
-
-use crate::{
-    data::{Direction, CALIBRATION, DIRECTION, GUI_MENU, HEIGHT, INPUT, RAW_HEIGHT},
-    input::{Inputs, State},
+use ssd1306::{mode::BufferedGraphicsMode, prelude::*, I2CDisplayInterface, Ssd1306};
+
+use deposition::{
+    adc::{Characteristics, EfuseWords},
+    collision::CollisionDetector,
+    data::{
+        Direction, BUTTON_EVENTS, CALIBRATION, DIRECTION, FAULTS, GUI_MENU, HEIGHT,
+        HEIGHT_MEASURED, RAW_HEIGHT, REMINDER,
+    },
+    display_power::{self, DisplayPower, PowerState},
+    drive_state::DriveState,
+    event_log::{self, Event, PanicMessage},
+    format,
+    gui::{Frame, MainMenu},
+    heartbeat,
+    input::{Button, ButtonEvent},
+    motion::AbortReason,
+    operation_mode,
+    reminder::{Posture, ReminderTracker},
+    sampling::SlidingMedian,
+    statistics::STATISTICS,
     storage::CONFIGURATION,
+    string_format::format_truncated,
+    supervisor::{self, Supervisor, Task},
 };
 
+/// Pins of the photo-couplers that emulate the up and down buttons of the desk.
+const MOTOR_PINS: u32 = (1 << 25) | (1 << 26);
+
+/// Drives the motor outputs low without going through the drive task, so the desk stops
+/// even if the executor hangs.
+fn force_motor_outputs_low() {
+    // SAFETY: the set/clear registers only affect the pins in the mask and the drive task
+    // never drives these pins high while they are forced low because it is stuck or gone.
+    let gpio = unsafe { hal::peripherals::GPIO::steal() };
+    gpio.out_w1tc().write(|w| unsafe { w.bits(MOTOR_PINS) });
+    gpio.enable_w1ts().write(|w| unsafe { w.bits(MOTOR_PINS) });
+}
+
+/// Called by the exception handler of esp-backtrace before it prints the backtrace and
+/// halts the chip until the watchdog resets it.
+#[no_mangle]
+pub extern "Rust" fn custom_pre_backtrace() {
+    force_motor_outputs_low();
+}
+
+/// Message of the last panic, added to the crash event after the watchdog reset.
+#[ram(rtc_fast, persistent)]
+static mut PANIC_MESSAGE: PanicMessage = PanicMessage::new();
+
+/// Keeps the panic message for the event log, the flash cannot be written safely here.
+/// The chip halts until the watchdog resets it.
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    force_motor_outputs_low();
+    // SAFETY: the panic handler does not return and the message is only read at boot
+    unsafe { (*core::ptr::addr_of_mut!(PANIC_MESSAGE)).set(format_args!("{info}")) };
+    log::error!("{info}");
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Whether the previous run ended in a panic, an exception or a stall, i.e. the chip was
+/// reset by a watchdog.
+fn is_crash(reason: &SocResetReason) -> bool {
+    matches!(
+        reason,
+        SocResetReason::CoreMwdt0
+            | SocResetReason::CoreMwdt1
+            | SocResetReason::CoreRtcWdt
+            | SocResetReason::Cpu0Mwdt0
+            | SocResetReason::Cpu0RtcWdt
+            | SocResetReason::SysRtcWdt
+    )
+}
+
 async fn poll<T, E>(mut f: impl FnMut() -> nb::Result<T, E>) -> Result<T, E> {
     loop {
         match f() {
             Ok(ok) => break Ok(ok),
             Err(nb::Error::Other(err)) => break Err(err),
-            Err(nb::Error::WouldBlock) => {} // todo: do we want to keep a busy loop here? the measure task starved with this: `embassy_futures::yield_now().await,`
+            // A single conversion only takes a few microseconds, busy waiting is cheaper than
+            // yielding. The pacing between samples is done by the measure task's ticker.
+            Err(nb::Error::WouldBlock) => {}
         }
     }
 }
 
-fn compute_median(samples: &mut [u16]) -> u16 {
-    samples.sort_unstable();
-    let len = samples.len();
-    if len % 2 == 0 {
-        let right_mid = samples[len / 2];
-        let left_mid = samples[(len / 2) - 1];
-        (right_mid + left_mid) / 2
-    } else {
-        samples[len / 2]
-    }
-}
-
+/// The ADC is sampled once per period (1kHz), independent of how long the rest of the
+/// measure loop takes.
+const SAMPLE_PERIOD: Duration = Duration::from_hz(1000);
+/// Size of the sliding window the median is computed over, i.e. 64ms in release builds.
 const SAMPLE_COUNT: usize = if cfg!(debug_assertions) { 32 } else { 64 };
+/// A new height is published every 5 samples (200Hz).
+const PUBLISH_INTERVAL: u8 = 5;
+
+/// Readings this close to the ADC rails indicate that the potentiometer hit its end stop.
+const ADC_RAIL_MARGIN: u16 = 10;
+const ADC_MAX: u16 = 4095;
+
+fn is_saturated(reading: u16) -> bool {
+    reading <= ADC_RAIL_MARGIN || reading >= ADC_MAX - ADC_RAIL_MARGIN
+}
 
 type InputPin = hal::gpio::AnyInput<'static>;
 type OutputPin = hal::gpio::AnyOutput<'static>;
@@ -64,11 +139,15 @@ type OutputPin = hal::gpio::AnyOutput<'static>;
 async fn drive(mut up: OutputPin, mut down: OutputPin) {
     up.set_low();
     down.set_low();
+    let mut outputs = Direction::Stopped;
+    let mut moving_since = None;
     loop {
-        Timer::after(Duration::from_millis(5)).await;
-        let Some(direction) = DIRECTION.planned().await else {
+        heartbeat::DRIVE.beat();
+        let direction = DIRECTION.next_outputs(heartbeat::INTERVAL).await;
+        if direction == outputs {
+            DIRECTION.acknowledge(outputs);
             continue;
-        };
+        }
         log::info!("starting to drive in direction {direction}");
         match direction {
             Direction::Up => {
@@ -88,83 +167,162 @@ async fn drive(mut up: OutputPin, mut down: OutputPin) {
                 down.set_high();
             }
         }
-        DIRECTION.acknowledge(direction).await;
+        outputs = direction;
+        DIRECTION.acknowledge(outputs);
+        if let Some((previous, since)) = moving_since.replace((direction, Instant::now())) {
+            STATISTICS
+                .lock()
+                .await
+                .update(|s| s.add_motor_runtime(previous, since.elapsed()));
+        }
     }
 }
 
-#[embassy_executor::task]
-async fn read_input(up: InputPin, down: InputPin, pos1: InputPin, pos2: InputPin) {
-    struct DebouncedPin {
-        pin: InputPin,
-        debouncer: DebouncerStateful<u8, debouncr::Repeat2>,
-    }
+/// Interval between samples of a button while its state is not settled yet.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(5);
 
-    impl DebouncedPin {
-        fn new(pin: InputPin) -> Self {
-            let debouncer = debouncr::debounce_stateful_2(false);
-            Self { pin, debouncer }
-        }
+#[embassy_executor::task(pool_size = 4)]
+async fn read_input(mut pin: InputPin, button: Button) {
+    let mut debouncer = debouncr::debounce_stateful_2(false);
+    // the press that woke the display is not passed on, so its release must not be either
+    let mut waking = false;
 
-        fn update_input(&mut self, input: &mut State) {
-            let active = self.pin.is_low();
-            let Some(state) = self.debouncer.update(active) else {
-                return;
-            };
-            match state {
-                debouncr::Edge::Rising => input.press(),
-                debouncr::Edge::Falling => input.release(),
+    loop {
+        heartbeat::INPUT.beat();
+        // Buttons are active low. Waiting for the level opposite to the debounced state
+        // instead of an edge ensures no change is missed while the previous one is debounced.
+        let level_changed = async {
+            if debouncer.is_high() {
+                pin.wait_for_high().await;
+            } else {
+                pin.wait_for_low().await;
             }
+        };
+        if with_timeout(heartbeat::INTERVAL, level_changed)
+            .await
+            .is_err()
+        {
+            continue;
         }
-    }
-
-    let mut up = DebouncedPin::new(up);
-    let mut down = DebouncedPin::new(down);
-    let mut pos1 = DebouncedPin::new(pos1);
-    let mut pos2 = DebouncedPin::new(pos2);
-
-    let mut inputs = Inputs::default();
-
-    loop {
-        up.update_input(&mut inputs.up);
-        down.update_input(&mut inputs.down);
-        pos1.update_input(&mut inputs.pos1);
-        pos2.update_input(&mut inputs.pos2);
 
-        *INPUT.lock().await = inputs.clone();
+        loop {
+            Timer::after(DEBOUNCE_INTERVAL).await;
+            let active = pin.is_low();
+            if let Some(edge) = debouncer.update(active) {
+                let event = ButtonEvent {
+                    button,
+                    pressed: matches!(edge, debouncr::Edge::Rising),
+                    time: Instant::now(),
+                };
+                display_power::ACTIVITY.signal(());
+                match event {
+                    ButtonEvent { pressed: true, .. } if display_power::is_display_off() => {
+                        log::debug!("{button:?} pressed while display is off, only waking it");
+                        waking = true;
+                    }
+                    ButtonEvent { pressed: false, .. } if waking => waking = false,
+                    // Waiting for room would stall this task and its heartbeat while the
+                    // consumer is busy. Events are only piling up then, so dropping is fine.
+                    _ => {
+                        if BUTTON_EVENTS.try_send(event).is_err() {
+                            log::warn!("button event queue full, dropping {event:?}");
+                        }
+                    }
+                }
+            }
 
-        Timer::after(Duration::from_millis(5)).await;
+            if debouncer.is_high() == active {
+                break;
+            }
+        }
     }
 }
 
 #[embassy_executor::task]
-async fn measure_task(gpio34: Gpio34, adc: ADC1) {
-    measure(gpio34, adc).await.expect("measure task failed");
+async fn measure_task(mut gpio34: Gpio34, mut adc: ADC1, characteristics: Option<Characteristics>) {
+    loop {
+        // the driver is set up again after every failure to reset the ADC configuration
+        let mut adc1_config = AdcConfig::new();
+        let mut pin34 = adc1_config.enable_pin(gpio34, Attenuation::Attenuation11dB);
+        let mut adc1 = Adc::<ADC1>::new(&mut adc, adc1_config);
+
+        let result = measure(&mut adc1, &mut pin34, characteristics).await;
+        gpio34 = pin34.pin;
+        if let Err(e) = result {
+            *HEIGHT.lock().await = None;
+            supervisor::report(Task::Measure, e).await;
+        }
+    }
 }
 
-async fn measure(pin: Gpio34, adc: ADC1) -> Result<(), &'static str> {
-    let mut adc1_config = AdcConfig::new();
-    let mut pin34 = adc1_config.enable_pin(pin, Attenuation::Attenuation11dB);
-    let mut adc1 = Adc::<ADC1>::new(adc, adc1_config);
+fn read_adc_characteristics(efuse: &EFUSE) -> Characteristics {
+    let characteristics = Characteristics::from_efuse(EfuseWords {
+        blk0_rdata3: efuse.blk0_rdata3().read().bits(),
+        blk0_rdata4: efuse.blk0_rdata4().read().bits(),
+        blk3_rdata3: efuse.blk3_rdata3().read().bits(),
+    });
+    log::info!(
+        "ADC correction enabled using {:?} calibration data",
+        characteristics.source()
+    );
+    characteristics
+}
 
+async fn measure(
+    adc1: &mut Adc<'_, ADC1>,
+    pin34: &mut AdcPin<Gpio34, ADC1>,
+    characteristics: Option<Characteristics>,
+) -> Result<(), &'static str> {
     let mut calibration = CONFIGURATION.lock().await.get().calibration.clone();
 
+    let mut window = SlidingMedian::<SAMPLE_COUNT>::new();
+    let mut samples_since_publish = 0;
+    let mut ticker = Ticker::every(SAMPLE_PERIOD);
+
     let mut last_log = Instant::now();
+    let mut was_plausible = true;
     loop {
+        ticker.next().await;
+        heartbeat::MEASURE.beat();
+        window.push(read_sample(adc1, pin34).await?);
+
+        samples_since_publish += 1;
+        if samples_since_publish < PUBLISH_INTERVAL || !window.is_full() {
+            continue;
+        }
+        samples_since_publish = 0;
+
         if CALIBRATION.signaled() {
             calibration = CALIBRATION.wait().await;
         }
 
-        let pin25_value = read_sample(&mut adc1, &mut pin34).await?;
+        let Some(raw_value) = window.median() else {
+            continue;
+        };
+        let pin25_value = characteristics.map_or(raw_value, |c| c.millivolts(raw_value));
 
-        let value = calibration.transform(pin25_value);
+        let plausible = !is_saturated(raw_value) && calibration.is_within_range(pin25_value);
+        let value = plausible.then(|| calibration.transform(pin25_value));
+
+        if plausible != was_plausible {
+            if plausible {
+                log::info!("ADC reading {pin25_value} is plausible again");
+            } else {
+                log::warn!("implausible ADC reading {pin25_value}, height is unknown");
+            }
+            was_plausible = plausible;
+        }
 
         if log::log_enabled!(log::Level::Trace) && last_log.elapsed() > Duration::from_millis(250) {
-            log::trace!("new height {} (={pin25_value}) measured", value.as_mm());
+            log::trace!(
+                "new height {:?} (={pin25_value}) measured",
+                value.map(|v| v.as_mm())
+            );
             last_log = Instant::now();
         }
         *HEIGHT.lock().await = value;
+        HEIGHT_MEASURED.signal(());
         RAW_HEIGHT.signal(pin25_value);
-        Ticker::every(Duration::from_millis(5)).next().await;
     }
 }
 
@@ -172,21 +330,105 @@ async fn read_sample<'a>(
     adc1: &mut Adc<'a, ADC1>,
     pin34: &mut AdcPin<Gpio34, ADC1>,
 ) -> Result<u16, &'static str> {
-    let mut samples = heapless::Vec::<_, SAMPLE_COUNT>::new();
-    for _ in 0..samples.capacity() {
-        let sample = poll(|| adc1.read_oneshot(pin34))
-            .await
-            .map_err(|_| "failed to read ADC value")?;
+    poll(|| adc1.read_oneshot(pin34))
+        .await
+        .map_err(|_| "failed to read ADC value")
+}
+
+/// Sitting and standing time is tracked with a resolution of 10s.
+const POSTURE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Tracks the sitting and standing time for the statistics and the reminders.
+#[embassy_executor::task]
+async fn posture_task() {
+    let mut tracker = ReminderTracker::new();
+    let mut ticker = Ticker::every(POSTURE_CHECK_INTERVAL);
+    loop {
+        ticker.next().await;
+        let (heights, schedule) = {
+            let mut conf = CONFIGURATION.lock().await;
+            let data = conf.get();
+            (data.sit_stand_heights(), data.reminder)
+        };
+        let height = *HEIGHT.lock().await;
+        let posture = heights
+            .zip(height)
+            .map(|((sitting, standing), height)| Posture::of(height, sitting, standing));
+        if let Some(posture) = posture {
+            STATISTICS
+                .lock()
+                .await
+                .update(|s| s.add_posture_time(posture, POSTURE_CHECK_INTERVAL));
+        }
+
+        if let Some(reminder) = tracker.update(Instant::now(), posture, schedule) {
+            log::info!(
+                "{} for {}min, reminding to change to {}",
+                reminder.posture.other(),
+                reminder.elapsed.as_secs() / 60,
+                reminder.posture
+            );
+            REMINDER.signal(reminder);
+        }
+    }
+}
 
-        samples.push(sample).map_err(|_| "failed to store sample")?;
+/// The speed of the desk is checked this often.
+const COLLISION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stops the desk if it slows down while moving, i.e. if it runs into an obstacle.
+#[embassy_executor::task]
+async fn collision_task() {
+    let mut detector = CollisionDetector::new();
+    let mut ticker = Ticker::every(COLLISION_CHECK_INTERVAL);
+    loop {
+        ticker.next().await;
+        let moving = match DIRECTION.state().await {
+            DriveState::Moving(direction) => Some(direction),
+            _ => None,
+        };
+        let height = *HEIGHT.lock().await;
+        let sensitivity = CONFIGURATION.lock().await.get().collision_sensitivity;
+        if detector.update(Instant::now(), moving, height, sensitivity) {
+            DIRECTION.abort(AbortReason::Collision).await;
+        }
     }
+}
 
-    Ok(compute_median(&mut samples))
+/// Statistics are written to flash at most this often to limit wear.
+const STATISTICS_PERSIST_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+#[embassy_executor::task]
+async fn persist_statistics() {
+    let mut ticker = Ticker::every(STATISTICS_PERSIST_INTERVAL);
+    loop {
+        ticker.next().await;
+        let mut statistics = STATISTICS.lock().await;
+        statistics.get().dump();
+        statistics.persist();
+    }
 }
 
+type Display = Ssd1306<
+    I2CInterface<I2C<'static, hal::peripherals::I2C0, hal::Blocking>>,
+    DisplaySize128x64,
+    BufferedGraphicsMode<DisplaySize128x64>,
+>;
+
 #[embassy_executor::task]
 async fn display_task(i2c: I2C<'static, hal::peripherals::I2C0, hal::Blocking>) {
-    display(i2c).await.expect("display task failed");
+    let interface = I2CDisplayInterface::new(i2c);
+    let mut display = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate180)
+        .into_buffered_graphics_mode();
+    let mut menu = None;
+    let mut power = DisplayPower::new(Instant::now());
+
+    loop {
+        if let Err(e) = render(&mut display, &mut menu, &mut power).await {
+            log::error!("{e}");
+            supervisor::report(Task::Display, "display failed").await;
+        }
+    }
 }
 
 fn str_to_owned<const N: usize>(text: &str) -> String<N> {
@@ -194,45 +436,207 @@ fn str_to_owned<const N: usize>(text: &str) -> String<N> {
         .expect("Length of str exceeds String capacity")
 }
 
-async fn display(
-    i2c: I2C<'static, hal::peripherals::I2C0, hal::Blocking>,
+/// Display power is managed with this resolution.
+const DISPLAY_POWER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Initializes the display and renders every menu. The last menu is kept in `menu`, so it
+/// can be shown again after the display was reinitialized.
+async fn render(
+    display: &mut Display,
+    menu: &mut Option<MainMenu>,
+    power: &mut DisplayPower,
 ) -> Result<(), String<150>> {
-    let interface = I2CDisplayInterface::new(i2c);
-    let mut display = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate180)
-        .into_buffered_graphics_mode();
     display
         .init()
         .map_err(|e| format!(150, "display initialization failed: {e:?}"))?;
+    let mut power_state = PowerState::On;
+    display_power::set_display_off(false);
+
+    // Whatever the display showed before the initialization is unknown, so start blank.
+    display
+        .clear(BinaryColor::Off)
+        .map_err(|e| format!(150, "clearing display failed: {e:?}"))?;
+    display
+        .flush()
+        .map_err(|e| format!(150, "flushing failed: {e:?}"))?;
+    let mut shown = Frame::new();
+    let mut frame = Frame::new();
+    let mut shown_shift = None;
+    let mut redraw = true;
 
     loop {
-        let menu = GUI_MENU.wait().await;
-
-        display
-            .clear(BinaryColor::Off)
-            .map_err(|e| format!(150, "clearing display failed: {e:?}"))?;
-        menu.display(&mut display).await.map_err(str_to_owned)?;
-        display
-            .flush()
-            .map_err(|e| format!(150, "flushing failed: {e:?}"))?;
+        let (timeout, pixel_shift) = {
+            let mut conf = CONFIGURATION.lock().await;
+            let data = conf.get();
+            (data.display_timeout, data.pixel_shift)
+        };
+
+        let now = Instant::now();
+        power.observe_height(now, *HEIGHT.lock().await);
+        let new_power_state = power.state(now, timeout);
+        if new_power_state != power_state {
+            log::debug!("display power {power_state:?} -> {new_power_state:?}");
+            set_power_state(display, new_power_state)?;
+            power_state = new_power_state;
+        }
+
+        let shift = match menu {
+            Some(MainMenu::Start(_)) if pixel_shift => Some(display_power::pixel_shift(now)),
+            _ => None,
+        };
+        if let Some(menu) = menu.as_ref().filter(|_| redraw || shift != shown_shift) {
+            let _ = frame.clear(BinaryColor::Off);
+            match shift {
+                Some((x, y)) => {
+                    let area = Rectangle::new(
+                        Point::new(1 + i32::from(x), 1 + i32::from(y)),
+                        frame.bounding_box().size - Size::new(2, 2),
+                    );
+                    menu.display(&mut frame.cropped(&area)).await
+                }
+                None => menu.display(&mut frame).await,
+            }
+            .map_err(str_to_owned)?;
+
+            // Only the changed pixels are drawn, so the buffered display flushes just the
+            // area around them instead of the whole frame.
+            if frame != shown {
+                display
+                    .draw_iter(frame.changes(&shown))
+                    .map_err(|e| format!(150, "drawing failed: {e:?}"))?;
+                display
+                    .flush()
+                    .map_err(|e| format!(150, "flushing failed: {e:?}"))?;
+                shown.clone_from(&frame);
+            }
+            shown_shift = shift;
+        }
+
+        // The timer only re-evaluates the display power, the menu is drawn again when it
+        // changed or the start screen needs to be shifted.
+        redraw = match select3(
+            GUI_MENU.wait(),
+            display_power::ACTIVITY.wait(),
+            Timer::after(DISPLAY_POWER_INTERVAL),
+        )
+        .await
+        {
+            Either3::First(new_menu) => {
+                let refresh = menu
+                    .as_ref()
+                    .is_some_and(|menu| new_menu.is_refresh_of(menu));
+                if !refresh {
+                    power.activity(Instant::now());
+                }
+                *menu = Some(new_menu);
+                true
+            }
+            Either3::Second(()) => {
+                power.activity(Instant::now());
+                false
+            }
+            Either3::Third(()) => false,
+        };
     }
 }
 
+fn set_power_state(display: &mut Display, state: PowerState) -> Result<(), String<150>> {
+    display_power::set_display_off(state == PowerState::Off);
+    let brightness = match state {
+        PowerState::On => Brightness::NORMAL,
+        PowerState::Dimmed | PowerState::Off => Brightness::DIMMEST,
+    };
+    display
+        .set_brightness(brightness)
+        .map_err(|e| format!(150, "setting display brightness failed: {e:?}"))?;
+    display
+        .set_display_on(state != PowerState::Off)
+        .map_err(|e| format!(150, "switching display on/off failed: {e:?}"))
+}
+
 #[embassy_executor::task]
 async fn run() {
-    operation_mode::run().await.expect("run task failed");
+    loop {
+        let Err(e) = operation_mode::run().await;
+        supervisor::report(Task::OperationMode, e).await;
+    }
+}
+
+/// Central handler for task failures. Stops the desk and resets the chip if a task keeps
+/// failing after being restarted.
+#[embassy_executor::task]
+async fn supervise() {
+    let mut supervisor = Supervisor::new();
+    loop {
+        let fault = FAULTS.receive().await;
+        DIRECTION.fault().await;
+        event_log::record(Event::TaskFailed(fault.task));
+
+        if supervisor.register(fault.task, Instant::now()) {
+            log::error!(
+                "{:?} task keeps failing ({}), resetting chip",
+                fault.task,
+                fault.error
+            );
+            force_motor_outputs_low();
+            hal::reset::software_reset();
+        }
+        supervisor::recover(fault).await;
+    }
+}
+
+/// The watchdog resets the chip if it is not fed for this long.
+const WATCHDOG_TIMEOUT_MS: u64 = 2000;
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Feeds the watchdog as long as all monitored tasks make progress.
+#[embassy_executor::task]
+async fn watchdog(mut rtc: Rtc<'static>) {
+    rtc.rwdt.set_timeout(WATCHDOG_TIMEOUT_MS.millis());
+    rtc.rwdt.enable();
+    loop {
+        Timer::after(HEALTH_CHECK_INTERVAL).await;
+        match heartbeat::stalled_task(Instant::now()) {
+            None => rtc.rwdt.feed(),
+            Some(task) => {
+                log::error!("{task} task stalled, stopping motors until the watchdog resets");
+                force_motor_outputs_low();
+            }
+        }
+    }
 }
 
 #[main]
 async fn main(spawner: embassy_executor::Spawner) {
+    force_motor_outputs_low();
     init_logger(log::LevelFilter::Trace);
     log::info!("init!");
     let peripherals = Peripherals::take();
     let system = SystemControl::new(peripherals.SYSTEM);
     let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
 
+    let rtc = Rtc::new(peripherals.LPWR, None);
+
     let timer_group0 = TimerGroup::new_async(peripherals.TIMG0, &clocks);
     esp_hal_embassy::init(&clocks, timer_group0);
 
+    let reason = get_reset_reason(get_core());
+    let reset_reason = match &reason {
+        Some(reason) => format_truncated(format_args!("{reason:?}")),
+        None => format_truncated(format_args!("unknown")),
+    };
+    // SAFETY: the panic handler is the only other user and it does not return
+    let message = unsafe { (*core::ptr::addr_of_mut!(PANIC_MESSAGE)).take() };
+    // the panic handler cannot write to flash safely, so the crash is recorded now, still
+    // counting towards the previous boot
+    if reason.as_ref().is_some_and(is_crash) {
+        event_log::record(Event::Crash {
+            reset_reason: reset_reason.clone(),
+            message,
+        });
+    }
+    event_log::record(Event::Boot { reset_reason });
+
     let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
     let adc = peripherals.ADC1;
     // Create a new peripheral object with the described wiring
@@ -254,11 +658,22 @@ async fn main(spawner: embassy_executor::Spawner) {
     let up = OutputPin::new(io.pins.gpio25, Level::Low);
     let down = OutputPin::new(io.pins.gpio26, Level::Low);
 
-    spawner.spawn(measure_task(height_meter, adc)).unwrap();
-    spawner.spawn(display_task(i2c)).unwrap();
+    let characteristics =
+        cfg!(feature = "adc-correction").then(|| read_adc_characteristics(&peripherals.EFUSE));
+
     spawner
-        .spawn(read_input(btn_up, btn_down, btn_pos1, btn_pos2))
+        .spawn(measure_task(height_meter, adc, characteristics))
         .unwrap();
+    spawner.spawn(display_task(i2c)).unwrap();
+    spawner.spawn(read_input(btn_up, Button::Up)).unwrap();
+    spawner.spawn(read_input(btn_down, Button::Down)).unwrap();
+    spawner.spawn(read_input(btn_pos1, Button::Pos1)).unwrap();
+    spawner.spawn(read_input(btn_pos2, Button::Pos2)).unwrap();
     spawner.spawn(drive(up, down)).unwrap();
+    spawner.spawn(posture_task()).unwrap();
+    spawner.spawn(collision_task()).unwrap();
+    spawner.spawn(persist_statistics()).unwrap();
     spawner.spawn(run()).unwrap();
+    spawner.spawn(supervise()).unwrap();
+    spawner.spawn(watchdog(rtc)).unwrap();
 }
@@ -0,0 +1,394 @@
+//! Motion commands for the drive task.
+//!
+//! Requesting a direction returns a [`Motion`] that can be awaited until the outputs were
+//! switched, the desk reached a target or the motion was aborted. The latency from a request
+//! to the switched outputs is bounded as follows:
+//!
+//! 1. [`DirectionControl::request`] updates the state and signals the drive task right away.
+//! 2. The drive task wakes up in [`DirectionControl::next_outputs`]. The executor is
+//!    cooperative, so this takes as long as the longest stretch another task runs without
+//!    awaiting, e.g. a blocking display flush or flash write. Reversals additionally wait
+//!    for [`crate::drive_state::DEAD_TIME`].
+//! 3. The drive task writes the pins and calls [`DirectionControl::acknowledge`] without
+//!    awaiting in between, which completes [`Motion::switched`].
+//!
+//! The drive task also returns from [`DirectionControl::next_outputs`] after its timeout, so
+//! a lost wake-up is caught by the next heartbeat at the latest.
+
+use core::{cell::RefCell, future::poll_fn, task::Poll};
+
+use embassy_futures::select::{select, Either};
+use embassy_sync::{
+    blocking_mutex::{self, raw::CriticalSectionRawMutex},
+    waitqueue::MultiWakerRegistration,
+};
+use embassy_time::{Duration, Instant, Ticker, Timer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{Direction, Millimeters, Mutex, Signal, HEIGHT},
+    drive_state::DriveState,
+};
+
+/// Futures waiting on motions at the same time, more are woken spuriously but still work.
+const MAX_WAITERS: usize = 4;
+
+/// Why a motion ended before it was completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbortReason {
+    HeightUnknown,
+    /// The drive state does not allow the motion right now.
+    Rejected,
+    /// Another motion was requested.
+    Superseded,
+    /// A task failed.
+    Fault,
+    /// The desk did not arrive in time.
+    Timeout,
+    /// The desk slowed down as if it ran into an obstacle.
+    Collision,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pending,
+    Switched,
+    Aborted(AbortReason),
+}
+
+/// The most recently requested motion.
+#[derive(Debug, Clone, Copy)]
+struct Command {
+    id: u32,
+    direction: Direction,
+    status: Status,
+}
+
+impl Command {
+    fn status(&self, id: u32) -> Status {
+        if self.id == id {
+            self.status
+        } else {
+            Status::Aborted(AbortReason::Superseded)
+        }
+    }
+}
+
+struct Commands {
+    current: Command,
+    /// Every waiting [`Motion`] future, so all of them see a status change.
+    waiters: MultiWakerRegistration<MAX_WAITERS>,
+}
+
+pub struct DirectionControl {
+    state: Mutex<DriveState>,
+    commands: blocking_mutex::Mutex<CriticalSectionRawMutex, RefCell<Commands>>,
+    /// Wakes the drive task when the state changed.
+    changed: Signal<()>,
+}
+
+impl DirectionControl {
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(DriveState::Idle),
+            commands: blocking_mutex::Mutex::new(RefCell::new(Commands {
+                current: Command {
+                    id: 0,
+                    direction: Direction::Stopped,
+                    status: Status::Switched,
+                },
+                waiters: MultiWakerRegistration::new(),
+            })),
+            changed: Signal::new(),
+        }
+    }
+
+    /// Requests to drive in `direction`. Invalid requests are rejected, which the returned
+    /// motion reports as [`AbortReason::Rejected`].
+    pub async fn request(&'static self, direction: Direction) -> Motion {
+        log::debug!("driving in direction {direction} requested.");
+        let mut state = self.state.lock().await;
+        let command = match state.request(direction, Instant::now()) {
+            Ok(new_state) => {
+                *state = new_state;
+                // the owner of the previous motion learns that it was superseded
+                let id = self.update_command(|command| {
+                    *command = Command {
+                        id: command.id.wrapping_add(1),
+                        direction,
+                        status: Status::Pending,
+                    };
+                    command.id
+                });
+                self.changed.signal(());
+                Ok(id)
+            }
+            Err(e) => {
+                log::warn!("rejected driving in direction {direction}: {e}");
+                Err(AbortReason::Rejected)
+            }
+        };
+        Motion {
+            control: self,
+            command,
+            direction,
+        }
+    }
+
+    pub async fn state(&self) -> DriveState {
+        *self.state.lock().await
+    }
+
+    /// Stops immediately and aborts the current motion.
+    pub async fn fault(&self) {
+        let mut state = self.state.lock().await;
+        *state = state.fault();
+        self.update_command(|command| command.status = Status::Aborted(AbortReason::Fault));
+        self.changed.signal(());
+    }
+
+    /// Stops and aborts the current motion for `reason`.
+    pub async fn abort(&self, reason: AbortReason) {
+        let mut state = self.state.lock().await;
+        match state.request(Direction::Stopped, Instant::now()) {
+            Ok(new_state) => *state = new_state,
+            Err(e) => log::warn!("failed to stop: {e}"),
+        }
+        self.update_command(|command| command.status = Status::Aborted(reason));
+        self.changed.signal(());
+    }
+
+    pub async fn clear_fault(&self) {
+        let mut state = self.state.lock().await;
+        *state = state.clear_fault(Instant::now());
+        self.changed.signal(());
+    }
+
+    /// Waits until the state changed, the dead time passed or `timeout` expired and returns
+    /// how the outputs must be switched now.
+    ///
+    /// The state counts the outputs as switched right away, so that a request arriving in the
+    /// meantime cannot skip the dead time. The caller must switch them without delay and
+    /// [`Self::acknowledge`] them afterwards.
+    pub async fn next_outputs(&self, timeout: Duration) -> Direction {
+        let timeout = Instant::now() + timeout;
+        let deadline = self
+            .state
+            .lock()
+            .await
+            .deadline()
+            .map_or(timeout, |deadline| deadline.min(timeout));
+        select(self.changed.wait(), Timer::at(deadline)).await;
+
+        let mut state = self.state.lock().await;
+        *state = state.tick(Instant::now());
+        let outputs = state.outputs();
+        *state = state.outputs_switched();
+        outputs
+    }
+
+    /// The outputs were switched to `outputs`, which completes a pending motion in this
+    /// direction.
+    pub fn acknowledge(&self, outputs: Direction) {
+        self.update_command(|command| {
+            if command.status == Status::Pending && command.direction == outputs {
+                command.status = Status::Switched;
+            }
+        });
+    }
+
+    /// Changes the current command and wakes everyone waiting on a motion.
+    fn update_command<R>(&self, f: impl FnOnce(&mut Command) -> R) -> R {
+        self.commands.lock(|commands| {
+            let mut commands = commands.borrow_mut();
+            let result = f(&mut commands.current);
+            commands.waiters.wake();
+            result
+        })
+    }
+
+    /// Waits until `f` returns a value for the status of command `id`.
+    async fn wait_for<R>(&self, id: u32, mut f: impl FnMut(Status) -> Option<R>) -> R {
+        poll_fn(|cx| {
+            self.commands.lock(|commands| {
+                let mut commands = commands.borrow_mut();
+                match f(commands.current.status(id)) {
+                    Some(result) => Poll::Ready(result),
+                    None => {
+                        commands.waiters.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+}
+
+/// Handle of a requested motion. Any number of tasks may wait on it.
+#[derive(Clone, Copy)]
+pub struct Motion {
+    control: &'static DirectionControl,
+    command: Result<u32, AbortReason>,
+    direction: Direction,
+}
+
+impl Motion {
+    /// Waits until the drive task switched the outputs for this motion.
+    pub async fn switched(&self) -> Result<(), AbortReason> {
+        let id = self.command?;
+        self.control
+            .wait_for(id, |status| match status {
+                Status::Pending => None,
+                Status::Switched => Some(Ok(())),
+                Status::Aborted(reason) => Some(Err(reason)),
+            })
+            .await
+    }
+
+    /// Waits until the motion is aborted, e.g. because of a fault.
+    pub async fn aborted(&self) -> AbortReason {
+        let id = match self.command {
+            Ok(id) => id,
+            Err(reason) => return reason,
+        };
+        self.control
+            .wait_for(id, |status| match status {
+                Status::Aborted(reason) => Some(reason),
+                Status::Pending | Status::Switched => None,
+            })
+            .await
+    }
+
+    /// Waits until the desk is within `delta` of `target` or moved past it.
+    pub async fn reached(
+        &self,
+        target: Millimeters,
+        delta: Millimeters,
+    ) -> Result<(), AbortReason> {
+        let mut ticker = Ticker::every(Duration::from_millis(10));
+        self.switched().await?;
+        loop {
+            let height = (*HEIGHT.lock().await).ok_or(AbortReason::HeightUnknown)?;
+            if self.direction.reached(height, target, delta) {
+                return Ok(());
+            }
+            if let Either::First(reason) = select(self.aborted(), ticker.next()).await {
+                return Err(reason);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{future::Future, pin::pin};
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Wake, Waker},
+    };
+
+    use embassy_futures::{block_on, poll_once};
+    use embassy_time::MockDriver;
+
+    use crate::drive_state::DEAD_TIME;
+
+    use super::*;
+
+    fn next_outputs(control: &DirectionControl) -> Direction {
+        block_on(control.next_outputs(Duration::from_secs(1)))
+    }
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn switched_after_acknowledge() {
+        static CONTROL: DirectionControl = DirectionControl::new();
+        let motion = block_on(CONTROL.request(Direction::Up));
+        let mut switched = pin!(motion.switched());
+        assert!(poll_once(switched.as_mut()).is_pending());
+
+        assert_eq!(next_outputs(&CONTROL), Direction::Up);
+        // the drive task did not write the pins yet
+        assert!(poll_once(switched.as_mut()).is_pending());
+
+        CONTROL.acknowledge(Direction::Up);
+        assert_eq!(poll_once(switched), Poll::Ready(Ok(())));
+        assert!(poll_once(motion.aborted()).is_pending());
+    }
+
+    #[test]
+    fn every_waiter_is_woken() {
+        static CONTROL: DirectionControl = DirectionControl::new();
+        let motion = block_on(CONTROL.request(Direction::Down));
+        let mut switched = pin!(motion.switched());
+        let mut aborted = pin!(motion.aborted());
+        let flags: [Arc<Flag>; 2] = Default::default();
+        let wakers = flags.clone().map(Waker::from);
+        let mut contexts = wakers.each_ref().map(Context::from_waker);
+        assert!(switched.as_mut().poll(&mut contexts[0]).is_pending());
+        assert!(aborted.as_mut().poll(&mut contexts[1]).is_pending());
+
+        let next = block_on(CONTROL.request(Direction::Up));
+        assert!(flags.iter().all(|flag| flag.0.load(Ordering::SeqCst)));
+        assert_eq!(
+            switched.poll(&mut contexts[0]),
+            Poll::Ready(Err(AbortReason::Superseded))
+        );
+        assert_eq!(
+            aborted.poll(&mut contexts[1]),
+            Poll::Ready(AbortReason::Superseded)
+        );
+        assert!(poll_once(next.aborted()).is_pending());
+    }
+
+    #[test]
+    fn reversal_is_acknowledged_after_the_dead_time() {
+        static CONTROL: DirectionControl = DirectionControl::new();
+        let up = block_on(CONTROL.request(Direction::Up));
+        assert_eq!(next_outputs(&CONTROL), Direction::Up);
+
+        // requested before the drive task acknowledged the outputs for the first motion
+        let down = block_on(CONTROL.request(Direction::Down));
+        CONTROL.acknowledge(Direction::Up);
+        assert_eq!(
+            poll_once(up.switched()),
+            Poll::Ready(Err(AbortReason::Superseded))
+        );
+        assert!(poll_once(down.switched()).is_pending());
+
+        assert_eq!(next_outputs(&CONTROL), Direction::Stopped);
+        CONTROL.acknowledge(Direction::Stopped);
+        assert!(poll_once(down.switched()).is_pending());
+
+        MockDriver::get().advance(DEAD_TIME);
+        assert_eq!(next_outputs(&CONTROL), Direction::Down);
+        CONTROL.acknowledge(Direction::Down);
+        assert_eq!(poll_once(down.switched()), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    fn fault_aborts_and_rejects_motions() {
+        static CONTROL: DirectionControl = DirectionControl::new();
+        let motion = block_on(CONTROL.request(Direction::Up));
+        block_on(CONTROL.fault());
+        assert_eq!(poll_once(motion.aborted()), Poll::Ready(AbortReason::Fault));
+        assert_eq!(next_outputs(&CONTROL), Direction::Stopped);
+
+        let rejected = block_on(CONTROL.request(Direction::Down));
+        assert_eq!(
+            poll_once(rejected.switched()),
+            Poll::Ready(Err(AbortReason::Rejected))
+        );
+    }
+}
@@ -2,16 +2,28 @@ use core::{convert::Infallible, future::Future};
 
 use embassy_time::{Duration, Timer};
 
-use crate::input::Inputs;
+use crate::{input::Inputs, storage::CONFIGURATION};
 
 type Result<T = ()> = core::result::Result<T, &'static str>;
 
+mod button_mapping;
 mod calibration;
+mod choice;
+mod collision;
+mod display_settings;
+mod event_log;
+mod long_press;
 mod options;
+mod reminder;
+mod reset_drive;
+mod soft_approach;
 mod start;
+mod statistics;
+mod unit;
 
 pub async fn run() -> Result<Infallible> {
     let mut inputs = Inputs::new();
+    inputs.set_long_press(CONFIGURATION.lock().await.get().long_press);
     loop {
         start::run(&mut inputs).await?;
     }
@@ -0,0 +1,79 @@
+use crate::{
+    action::{Action, Trigger},
+    data::GUI_MENU,
+    gui::{ActionSelection, BindingMenu, ButtonMapping, Menu, MenuContent},
+    input::{Button, Inputs},
+    storage::CONFIGURATION,
+};
+
+use super::Result;
+
+pub async fn run(inputs: &mut Inputs) -> Result {
+    let actions = CONFIGURATION.lock().await.get().actions.clone();
+    let mut menu = BindingMenu::new(actions);
+    loop {
+        log::info!("running button mapping screen");
+
+        GUI_MENU.signal(
+            ButtonMapping {
+                menu: Menu::new(menu.clone()),
+            }
+            .into(),
+        );
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => menu.prev(),
+            Button::Down => menu.next(),
+            Button::Pos1 => return Ok(()),
+            Button::Pos2 => {
+                let trigger = menu.selected();
+                let Some(action) = select_action(inputs, menu.action()).await else {
+                    continue;
+                };
+                if let Err(e) = bind(trigger, action, &mut menu).await {
+                    log::warn!("failed to bind {trigger} to {action}: {e}");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn bind(trigger: Trigger, action: Action, menu: &mut BindingMenu) -> Result {
+    log::info!("binding {trigger} to {action}");
+
+    let mut res = Ok(());
+    let mut conf = CONFIGURATION.lock().await;
+    let actions = &conf
+        .update(|data| {
+            res = data.actions.set(trigger, action);
+        })
+        .actions;
+    menu.update_actions(actions);
+
+    res
+}
+
+/// Lets the user pick an action, `None` if the user cancelled.
+async fn select_action(inputs: &mut Inputs, mut selected: Action) -> Option<Action> {
+    loop {
+        log::info!("running action selection screen");
+
+        GUI_MENU.signal(
+            ActionSelection {
+                menu: Menu::new(selected),
+            }
+            .into(),
+        );
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => selected.prev(),
+            Button::Down => selected.next(),
+            Button::Pos1 => return None,
+            Button::Pos2 => return Some(selected),
+            _ => {}
+        }
+    }
+}
@@ -3,15 +3,21 @@ use embassy_time::{Duration, Instant, Ticker, Timer};
 
 use crate::{
     data::{Calibration, Millimeters, CALIBRATION, GUI_MENU, RAW_HEIGHT},
-    gui::{CalibrationMenu, CalibrationOptions, CalibrationPoint, Menu, MenuContent, Selected},
+    event_log::{self, Event},
+    gui::{
+        CalibrationMenu, CalibrationOptions, CalibrationPoint, Menu, MenuContent, PointAction,
+        PointOptions, Selected,
+    },
     input::{Button, Inputs},
     storage::CONFIGURATION,
+    unit::Unit,
 };
 
 use super::Result;
 
 pub async fn run(inputs: &mut Inputs) -> Result {
-    let mut menu = CalibrationMenu::new(Calibration::new());
+    let unit = CONFIGURATION.lock().await.get().unit;
+    let mut menu = CalibrationMenu::new(Calibration::new(), unit);
     loop {
         log::info!("running calibration screen");
         let calibration = CONFIGURATION.lock().await.get().calibration.clone();
@@ -38,15 +44,15 @@ pub async fn run(inputs: &mut Inputs) -> Result {
                     add_calibration_point(inputs).await?;
                 }
                 Selected::RemoveAll => {
-                    CONFIGURATION.lock().await.update(|data| {
-                        data.calibration.clear();
-                    });
+                    update_calibration(|calibration| {
+                        calibration.clear();
+                        Ok(())
+                    })
+                    .await?;
                 }
                 Selected::ShowOne => {
                     let Some(index) = menu.shown_index() else {continue;};
-                    CONFIGURATION.lock().await.update(|data| {
-                        data.calibration.remove(index);
-                    });
+                    point_options(inputs, index).await?;
                 }
             },
             _ => {}
@@ -54,36 +60,131 @@ pub async fn run(inputs: &mut Inputs) -> Result {
     }
 }
 
-async fn add_calibration_point(inputs: &mut Inputs) -> Result {
-    log::info!("running add calibration point screen");
+async fn point_options(inputs: &mut Inputs, index: usize) -> Result {
+    let mut selected = PointAction::EditHeight;
+    loop {
+        log::info!("running calibration point options screen");
 
-    let adc = RAW_HEIGHT.wait().await;
+        GUI_MENU.signal(
+            PointOptions {
+                menu: Menu::new(selected),
+            }
+            .into(),
+        );
 
-    let mut height = Millimeters::from_mm(1000);
-    loop {
-        GUI_MENU.signal(CalibrationPoint { adc, height }.into());
         inputs.wait_all_released().await;
         match inputs.wait_for_single_press().await {
-            Button::Up => height = button_held(adc, height, Button::Up, inputs).await,
-            Button::Down => height = button_held(adc, height, Button::Down, inputs).await,
+            Button::Up => selected.prev(),
+            Button::Down => selected.next(),
             Button::Pos1 => return Ok(()),
-            Button::Pos2 => break,
+            Button::Pos2 => {
+                match selected {
+                    PointAction::EditHeight => edit_calibration_point(inputs, index, false).await?,
+                    PointAction::RecaptureAdc => {
+                        edit_calibration_point(inputs, index, true).await?
+                    }
+                    PointAction::Delete => {
+                        update_calibration(|calibration| {
+                            calibration.remove(index);
+                            Ok(())
+                        })
+                        .await?
+                    }
+                }
+                return Ok(());
+            }
             _ => {}
         }
     }
+}
+
+async fn add_calibration_point(inputs: &mut Inputs) -> Result {
+    log::info!("running add calibration point screen");
+
+    let adc = RAW_HEIGHT.wait().await;
+
+    let Some(height) = select_height(inputs, adc, Millimeters::from_mm(1000)).await else {
+        return Ok(());
+    };
+
+    update_calibration(|calibration| calibration.insert(adc, height)).await
+}
+
+/// Re-captures the lowest calibration point at the current position, e.g. after a reset drive.
+pub async fn recalibrate_lowest(inputs: &mut Inputs) -> Result {
+    log::info!("recalibrating lowest point");
+    if CONFIGURATION.lock().await.get().calibration.is_empty() {
+        add_calibration_point(inputs).await
+    } else {
+        edit_calibration_point(inputs, 0, true).await
+    }
+}
 
-    let mut res = Ok(());
-    let cali = CONFIGURATION
+async fn edit_calibration_point(inputs: &mut Inputs, index: usize, recapture_adc: bool) -> Result {
+    log::info!("running edit calibration point screen");
+
+    let stored = CONFIGURATION
         .lock()
         .await
-        .update(|data| {
-            res = data.calibration.insert(adc, height);
-        })
+        .get()
         .calibration
-        .clone();
+        .get(index)
+        .copied();
+    let Some((stored_adc, stored_height)) = stored else {
+        return Ok(());
+    };
+
+    let adc = if recapture_adc {
+        RAW_HEIGHT.wait().await
+    } else {
+        stored_adc
+    };
+
+    let Some(height) = select_height(inputs, adc, stored_height).await else {
+        return Ok(());
+    };
 
-    res?;
+    update_calibration(|calibration| calibration.replace(index, adc, height)).await
+}
+
+/// Lets the user adjust the height for the given ADC value.
+///
+/// Returns `None` if the user cancelled.
+async fn select_height(
+    inputs: &mut Inputs,
+    adc: u16,
+    mut height: Millimeters,
+) -> Option<Millimeters> {
+    let unit = CONFIGURATION.lock().await.get().unit;
+    loop {
+        GUI_MENU.signal(CalibrationPoint { adc, height, unit }.into());
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => height = button_held(adc, height, unit, Button::Up, inputs).await,
+            Button::Down => height = button_held(adc, height, unit, Button::Down, inputs).await,
+            Button::Pos1 => return None,
+            Button::Pos2 => return Some(height),
+            _ => {}
+        }
+    }
+}
+
+/// Persists the modified calibration and hands it to the measure task.
+async fn update_calibration<F>(f: F) -> Result
+where
+    F: FnOnce(&mut Calibration) -> Result,
+{
+    let cali = {
+        let mut conf = CONFIGURATION.lock().await;
+        let mut cali = conf.get().calibration.clone();
+        f(&mut cali)?;
+        conf.update(|data| data.calibration = cali.clone());
+        cali
+    };
 
+    event_log::record(Event::CalibrationChanged {
+        points: cali.len().try_into().unwrap_or(u8::MAX),
+    });
     CALIBRATION.signal(cali);
 
     Ok(())
@@ -92,6 +193,7 @@ async fn add_calibration_point(inputs: &mut Inputs) -> Result {
 async fn button_held(
     adc: u16,
     mut height: Millimeters,
+    unit: Unit,
     btn: Button,
     inputs: &mut Inputs,
 ) -> Millimeters {
@@ -114,7 +216,7 @@ async fn button_held(
         loop {
             let height = ladder.repeat(&mut update_height);
 
-            GUI_MENU.signal(CalibrationPoint { adc, height }.into());
+            GUI_MENU.signal(CalibrationPoint { adc, height, unit }.into());
 
             ladder.accelerate();
             Ticker::every(Duration::from_hz(10)).next().await;
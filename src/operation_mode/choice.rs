@@ -0,0 +1,46 @@
+use core::fmt::Display;
+
+use crate::{
+    data::GUI_MENU,
+    gui::{ChoiceSetting, Choices, MainMenu, MenuContent},
+    input::{Button, Inputs},
+    storage::{InnerData, CONFIGURATION},
+};
+
+/// Lets the user pick one of `choices` for the setting `name`, starting at the current value
+/// returned by `get`. Returns the choice after storing it with `set`, `None` if the user went
+/// back.
+pub async fn run_choice<T, const N: usize>(
+    inputs: &mut Inputs,
+    name: &str,
+    choices: [T; N],
+    get: impl FnOnce(&InnerData) -> T,
+    set: impl FnOnce(&mut InnerData, T),
+) -> Option<T>
+where
+    T: Copy + Display + PartialEq,
+    ChoiceSetting<T, N>: Into<MainMenu>,
+{
+    let mut choices = Choices::new(choices, get(CONFIGURATION.lock().await.get()));
+    loop {
+        log::info!("running {name} settings screen");
+        GUI_MENU.signal(ChoiceSetting::new(choices).into());
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => choices.prev(),
+            Button::Down => choices.next(),
+            Button::Pos1 => return None,
+            Button::Pos2 => {
+                let selected = choices.selected();
+                log::info!("setting {name} to {selected}");
+                CONFIGURATION
+                    .lock()
+                    .await
+                    .update(|data| set(data, selected));
+                return Some(selected);
+            }
+            _ => {}
+        }
+    }
+}
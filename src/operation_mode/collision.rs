@@ -0,0 +1,14 @@
+use crate::{collision::Sensitivity, gui::SensitivityItem, input::Inputs};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    run_choice(
+        inputs,
+        "collision detection",
+        Sensitivity::CHOICES.map(SensitivityItem),
+        |data| SensitivityItem(data.collision_sensitivity),
+        |data, sensitivity| data.collision_sensitivity = sensitivity.0,
+    )
+    .await;
+}
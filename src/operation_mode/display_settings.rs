@@ -0,0 +1,62 @@
+use crate::{
+    data::GUI_MENU,
+    display_power::DisplayTimeout,
+    gui::{DisplaySetting, DisplaySettings, DisplaySettingsMenu, Menu, MenuContent, TimeoutItem},
+    input::{Button, Inputs},
+    storage::CONFIGURATION,
+};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    let mut menu = {
+        let mut conf = CONFIGURATION.lock().await;
+        let data = conf.get();
+        DisplaySettingsMenu {
+            timeout: data.display_timeout,
+            pixel_shift: data.pixel_shift,
+            selected: DisplaySetting::Timeout,
+        }
+    };
+    loop {
+        log::info!("running display settings screen");
+
+        GUI_MENU.signal(
+            DisplaySettings {
+                menu: Menu::new(menu),
+            }
+            .into(),
+        );
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => menu.prev(),
+            Button::Down => menu.next(),
+            Button::Pos1 => return,
+            Button::Pos2 => match menu.selected {
+                DisplaySetting::Timeout => {
+                    let timeout = run_choice(
+                        inputs,
+                        "display timeout",
+                        DisplayTimeout::CHOICES.map(TimeoutItem),
+                        |data| TimeoutItem(data.display_timeout),
+                        |data, timeout| data.display_timeout = timeout.0,
+                    )
+                    .await;
+                    if let Some(TimeoutItem(timeout)) = timeout {
+                        menu.timeout = timeout;
+                    }
+                }
+                DisplaySetting::PixelShift => {
+                    menu.pixel_shift = !menu.pixel_shift;
+                    log::info!("setting pixel shift to {}", menu.pixel_shift);
+                    CONFIGURATION
+                        .lock()
+                        .await
+                        .update(|data| data.pixel_shift = menu.pixel_shift);
+                }
+            },
+            _ => {}
+        }
+    }
+}
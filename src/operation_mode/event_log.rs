@@ -0,0 +1,34 @@
+use crate::{
+    data::GUI_MENU,
+    event_log,
+    gui::{EventLogMenu, EventLogScreen, Menu, MenuContent},
+    input::{Button, Inputs},
+};
+
+pub async fn run(inputs: &mut Inputs) {
+    let Some(mut menu) = event_log::with_log(EventLogMenu::new) else {
+        log::warn!("event log is busy");
+        return;
+    };
+    loop {
+        log::info!("running event log screen");
+
+        GUI_MENU.signal(
+            EventLogScreen {
+                menu: Menu::new(menu),
+            }
+            .into(),
+        );
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Up => menu.prev(),
+            Button::Down => menu.next(),
+            Button::Pos1 => return,
+            Button::Pos2 => {
+                event_log::with_log(|event_log| event_log.dump());
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use crate::input::{Inputs, LongPress};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    let long_press = run_choice(
+        inputs,
+        "long press",
+        LongPress::CHOICES,
+        |data| data.long_press,
+        |data, long_press| data.long_press = long_press,
+    )
+    .await;
+    if let Some(long_press) = long_press {
+        inputs.set_long_press(long_press);
+    }
+}
@@ -1,11 +1,14 @@
 use crate::{
-    data::{Direction, Millimeters, DIRECTION, GUI_MENU, HEIGHT},
-    gui::{Menu, MenuContent, OptionItem, Options, ResetDrive},
+    data::{Millimeters, GUI_MENU, HEIGHT},
+    gui::{Menu, MenuContent, OptionItem, Options},
     input::{Button, Inputs},
     storage::{InnerData, CONFIGURATION},
 };
 
-use super::{calibration, Result};
+use super::{
+    button_mapping, calibration, collision, display_settings, event_log, long_press, reminder,
+    reset_drive, soft_approach, statistics, unit, Result,
+};
 
 pub async fn run(inputs: &mut Inputs) -> Result {
     let mut selected = OptionItem::SavePos1;
@@ -28,27 +31,35 @@ pub async fn run(inputs: &mut Inputs) -> Result {
                 OptionItem::SavePos1 => save_pos(1, |d| &mut d.position_1).await,
                 OptionItem::SavePos2 => save_pos(2, |d| &mut d.position_2).await,
                 OptionItem::Calibration => calibration::run(inputs).await?,
-                OptionItem::ResetDrive => reset_drive(inputs).await,
+                OptionItem::ResetDrive => reset_drive::run(inputs).await?,
+                OptionItem::ButtonMapping => button_mapping::run(inputs).await?,
+                OptionItem::LongPress => long_press::run(inputs).await,
+                OptionItem::SoftApproach => soft_approach::run(inputs).await,
+                OptionItem::Collision => collision::run(inputs).await,
+                OptionItem::Unit => unit::run(inputs).await,
+                OptionItem::Display => display_settings::run(inputs).await,
+                OptionItem::Reminders => reminder::run(inputs).await,
+                OptionItem::Statistics => statistics::run(inputs).await,
+                OptionItem::EventLog => event_log::run(inputs).await,
+                OptionItem::Lock => {
+                    log::info!("locking buttons");
+                    CONFIGURATION.lock().await.update(|data| data.locked = true);
+                    return Ok(());
+                }
             },
             _ => {}
         }
     }
 }
 
-async fn reset_drive(inputs: &mut Inputs) {
-    DIRECTION.request(Direction::ResetDrive).await;
-
-    inputs.wait_all_released().await;
-    GUI_MENU.signal(ResetDrive.into());
-    inputs.wait_for_single_press().await;
-    DIRECTION.request(Direction::Stopped).await;
-}
-
 async fn save_pos<F>(pos_num: u8, f: F)
 where
     F: Fn(&mut InnerData) -> &mut Option<Millimeters>,
 {
-    let height = *HEIGHT.lock().await;
+    let Some(height) = *HEIGHT.lock().await else {
+        log::warn!("current height unknown, not saving position {pos_num}");
+        return;
+    };
     log::info!("saving position {pos_num} with height {}mm", height.as_mm());
     let mut conf = CONFIGURATION.lock().await;
     conf.update(|data| *f(data) = Some(height));
@@ -0,0 +1,14 @@
+use crate::{gui::ScheduleItem, input::Inputs, reminder::Schedule};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    run_choice(
+        inputs,
+        "reminder schedule",
+        Schedule::CHOICES.map(ScheduleItem),
+        |data| ScheduleItem(data.reminder),
+        |data, schedule| data.reminder = schedule.0,
+    )
+    .await;
+}
@@ -0,0 +1,97 @@
+use embassy_futures::select::{select3, Either3};
+use embassy_time::{Duration, Instant, Ticker};
+
+use crate::{
+    data::{Direction, Millimeters, DIRECTION, GUI_MENU, HEIGHT},
+    event_log::{self, Event},
+    gui::{ResetDrive, ResetDriveResult},
+    input::{Button, Inputs},
+    motion::AbortReason,
+    reset_drive::{Phase, ResetDriveMonitor},
+    storage::CONFIGURATION,
+    unit::Unit,
+};
+
+use super::{calibration, Result};
+
+pub async fn run(inputs: &mut Inputs) -> Result {
+    log::info!("running reset drive screen");
+    let (calibrated, unit) = {
+        let mut conf = CONFIGURATION.lock().await;
+        let data = conf.get();
+        (
+            data.calibration.first().map(|&(_, height)| height),
+            data.unit,
+        )
+    };
+    let motion = DIRECTION.request(Direction::ResetDrive).await;
+
+    inputs.wait_all_released().await;
+    let result = select3(
+        motion.aborted(),
+        inputs.wait_for_single_press(),
+        monitor(calibrated, unit),
+    )
+    .await;
+    DIRECTION.request(Direction::Stopped).await;
+
+    let (phase, lowest) = match result {
+        Either3::First(reason) => {
+            log::warn!("reset drive aborted: {reason:?}");
+            event_log::record(Event::DriveAborted(reason));
+            return Ok(());
+        }
+        Either3::Second(_) => {
+            log::info!("reset drive stopped by user");
+            return Ok(());
+        }
+        Either3::Third(result) => result,
+    };
+
+    if phase == Phase::TimedOut {
+        log::warn!("reset drive timed out");
+        event_log::record(Event::DriveAborted(AbortReason::Timeout));
+    }
+
+    GUI_MENU.signal(
+        ResetDriveResult {
+            phase,
+            lowest,
+            calibrated,
+            unit,
+        }
+        .into(),
+    );
+
+    inputs.wait_all_released().await;
+    if inputs.wait_for_single_press().await == Button::Pos2 && phase == Phase::Finished {
+        calibration::recalibrate_lowest(inputs).await?;
+    }
+    Ok(())
+}
+
+/// Shows the progress until the desk finished re-homing or the reset drive timed out.
+async fn monitor(lower_limit: Option<Millimeters>, unit: Unit) -> (Phase, Option<Millimeters>) {
+    let mut monitor = ResetDriveMonitor::new(Instant::now(), lower_limit);
+    let mut ticker = Ticker::every(Duration::from_millis(100));
+    loop {
+        let now = Instant::now();
+        let height = *HEIGHT.lock().await;
+        let phase = monitor.update(now, height);
+        log::debug!("reset drive {phase} at height {height:?}");
+        if let Phase::Finished | Phase::TimedOut = phase {
+            return (phase, monitor.lowest());
+        }
+
+        GUI_MENU.signal(
+            ResetDrive {
+                phase,
+                height,
+                elapsed: monitor.elapsed(now),
+                unit,
+            }
+            .into(),
+        );
+        ticker.next().await;
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{gui::SoftApproachItem, input::Inputs, soft_approach::SoftApproach};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    run_choice(
+        inputs,
+        "soft approach",
+        SoftApproach::CHOICES.map(SoftApproachItem),
+        |data| SoftApproachItem(data.soft_approach),
+        |data, soft_approach| data.soft_approach = soft_approach.0,
+    )
+    .await;
+}
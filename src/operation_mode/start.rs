@@ -1,13 +1,20 @@
 use core::cmp::Ordering;
 
-use embassy_futures::select::{select, select3};
-use embassy_time::{Duration, Ticker, Timer};
+use embassy_futures::select::{select, select3, Either, Either3};
+use embassy_time::{Duration, Instant, Ticker, Timer};
 
 use crate::{
-    data::{Direction, Millimeters, Mutex, DIRECTION, GUI_MENU, HEIGHT},
-    gui::Start,
-    input::{Button, Inputs},
-    storage::CONFIGURATION,
+    action::Action,
+    data::{Direction, Millimeters, DIRECTION, GUI_MENU, HEIGHT, HEIGHT_MEASURED, REMINDER},
+    event_log::{self, Event},
+    gui::{PositionSaved, Progress, ReminderPrompt, Start},
+    input::{Button, Gesture, Inputs},
+    motion::{AbortReason, Motion},
+    reminder::{Posture, Reminder},
+    soft_approach::{Approach, Step},
+    speed::SpeedEstimator,
+    statistics::STATISTICS,
+    storage::{InnerData, CONFIGURATION},
 };
 
 use super::{options, refresh_gui, Result};
@@ -16,113 +23,322 @@ pub async fn run(inputs: &mut Inputs) -> Result {
     loop {
         log::info!("running start screen");
         wait_for_first_measurement().await;
-        start_gui(Direction::Stopped).await;
-        inputs.wait_all_released().await;
-        match inputs.wait_for_press().await {
-            Button::UpAndDown => {
-                options::run(inputs).await?;
-            }
-            Button::Up => {
-                drive_direction(inputs, Direction::Up, Button::Up).await;
-            }
-            Button::Down => {
-                drive_direction(inputs, Direction::Down, Button::Down).await;
+        start_gui().await;
+        let action = match select(wait_for_action(inputs), REMINDER.wait()).await {
+            Either::First(action) => action,
+            Either::Second(reminder) => {
+                remind(inputs, reminder).await;
+                continue;
             }
-            Button::Pos1 => {
+        };
+        if CONFIGURATION.lock().await.get().locked && action != Action::ToggleLock {
+            log::info!("buttons locked, ignoring action {action}");
+            continue;
+        }
+        match action {
+            Action::None => {}
+            Action::DriveUp => drive_direction(inputs, Direction::Up).await,
+            Action::DriveDown => drive_direction(inputs, Direction::Down).await,
+            Action::GoToPosition1 => {
                 let Some(target_height) = CONFIGURATION.lock().await.get().position_1 else {
                     log::debug!("position 1 not saved.");
                     continue;
                 };
                 drive_to_position(inputs, target_height).await;
             }
-            Button::Pos2 => {
+            Action::GoToPosition2 => {
                 let Some(target_height) = CONFIGURATION.lock().await.get().position_2 else {
                     log::debug!("position 2 not saved.");
                     continue;
                 };
                 drive_to_position(inputs, target_height).await;
             }
-            _ => {}
+            Action::ToggleSitStand => {
+                let Some(target_height) = sit_stand_target().await else {
+                    continue;
+                };
+                drive_to_position(inputs, target_height).await;
+            }
+            Action::SavePosition1 => quick_save(inputs, 1, |d| &mut d.position_1).await,
+            Action::SavePosition2 => quick_save(inputs, 2, |d| &mut d.position_2).await,
+            Action::OpenOptions => options::run(inputs).await?,
+            Action::ToggleLock => toggle_lock().await,
+            Action::Stop => {
+                DIRECTION.request(Direction::Stopped).await;
+            }
         }
     }
 }
 
-async fn drive_direction(inputs: &mut Inputs, direction: Direction, button: Button) {
-    DIRECTION.request(direction).await;
-    select(
-        inputs.wait_for_release(button),
-        refresh_gui(|| start_gui(direction)),
+async fn wait_for_action(inputs: &mut Inputs) -> Action {
+    loop {
+        let gesture = inputs.wait_for_gesture().await;
+        let action = CONFIGURATION
+            .lock()
+            .await
+            .get()
+            .actions
+            .triggered_by(gesture);
+        if let Some(action) = action {
+            log::debug!("{gesture:?} triggered action {action}");
+            break action;
+        }
+    }
+}
+
+async fn drive_direction(inputs: &mut Inputs, direction: Direction) {
+    let motion = DIRECTION.request(direction).await;
+    STATISTICS.lock().await.update(|s| s.manual_moves += 1);
+    let result = select3(
+        motion.aborted(),
+        wait_for_release(inputs),
+        refresh_gui(start_gui),
     )
     .await;
+    if let Either3::First(reason) = result {
+        log::warn!("driving in direction {direction} aborted: {reason:?}");
+        event_log::record(Event::DriveAborted(reason));
+        if reason == AbortReason::Collision {
+            back_off(direction).await;
+            // the button is still held, it must not start driving into the obstacle again
+            select(wait_for_release(inputs), refresh_gui(start_gui)).await;
+        }
+    }
     DIRECTION.request(Direction::Stopped).await;
 }
 
-async fn drive_to_position(inputs: &mut Inputs, target_height: Millimeters) {
-    const ALLOWED_DELTA_IN_STANDSTILL: Millimeters = Millimeters::from_mm(2);
-    const ALLOWED_DELTA_IN_MOVEMENT: Millimeters = Millimeters::from_mm(18);
-    let current_height = *HEIGHT.lock().await;
-    let direction;
-    let on_the_way = match current_height.cmp_fuzzy_eq(target_height, ALLOWED_DELTA_IN_STANDSTILL) {
-        Ordering::Equal => return,
-        Ordering::Less => {
-            direction = Direction::Up;
-            |current_height: Millimeters, target_height| {
-                current_height
-                    .cmp_fuzzy_eq(target_height, ALLOWED_DELTA_IN_MOVEMENT)
-                    .is_lt()
-            }
-        }
-        Ordering::Greater => {
-            direction = Direction::Down;
-            |current_height: Millimeters, target_height| {
-                current_height
-                    .cmp_fuzzy_eq(target_height, ALLOWED_DELTA_IN_MOVEMENT)
-                    .is_gt()
-            }
+async fn wait_for_release(inputs: &mut Inputs) {
+    while !matches!(inputs.wait_for_gesture().await, Gesture::Released(_)) {}
+}
+
+/// Standing height when the desk is closer to the sitting height and vice versa.
+async fn sit_stand_target() -> Option<Millimeters> {
+    let Some((sitting, standing)) = CONFIGURATION.lock().await.get().sit_stand_heights() else {
+        log::debug!("sitting and standing position not saved.");
+        return None;
+    };
+    let Some(height) = *HEIGHT.lock().await else {
+        log::warn!("current height unknown, cannot toggle sitting and standing.");
+        return None;
+    };
+
+    Some(match Posture::of(height, sitting, standing) {
+        Posture::Sitting => standing,
+        Posture::Standing => sitting,
+    })
+}
+
+/// Shows the reminder and offers to move to the other posture. The desk is never moved
+/// without confirmation.
+async fn remind(inputs: &mut Inputs, reminder: Reminder) {
+    const REMINDER_TIMEOUT: Duration = Duration::from_secs(60);
+
+    let target_height = {
+        let mut conf = CONFIGURATION.lock().await;
+        let data = conf.get();
+        data.sit_stand_heights()
+            .filter(|_| !data.locked)
+            .map(|(sitting, standing)| match reminder.posture {
+                Posture::Sitting => sitting,
+                Posture::Standing => standing,
+            })
+    };
+
+    GUI_MENU.signal(
+        ReminderPrompt {
+            reminder,
+            can_move: target_height.is_some(),
         }
+        .into(),
+    );
+
+    inputs.wait_all_released().await;
+    let press = select(
+        Timer::after(REMINDER_TIMEOUT),
+        inputs.wait_for_single_press(),
+    )
+    .await;
+    if let (Either::Second(Button::Pos2), Some(target_height)) = (press, target_height) {
+        log::info!("moving to {} position after reminder", reminder.posture);
+        drive_to_position(inputs, target_height).await;
+    }
+}
+
+async fn toggle_lock() {
+    let locked = CONFIGURATION
+        .lock()
+        .await
+        .update(|data| data.locked = !data.locked)
+        .locked;
+    log::info!("buttons {}", if locked { "locked" } else { "unlocked" });
+}
+
+/// Saves the current height and offers to undo it for a few seconds.
+async fn quick_save<F>(inputs: &mut Inputs, pos_num: u8, f: F)
+where
+    F: Fn(&mut InnerData) -> &mut Option<Millimeters>,
+{
+    const UNDO_TIMEOUT: Duration = Duration::from_secs(4);
+
+    let Some(height) = *HEIGHT.lock().await else {
+        log::warn!("current height unknown, not saving position {pos_num}");
+        return;
     };
+    log::info!("saving position {pos_num} with height {}mm", height.as_mm());
+    let mut previous = None;
+    let unit = CONFIGURATION
+        .lock()
+        .await
+        .update(|data| previous = f(data).replace(height))
+        .unit;
 
-    let check_height = || async move {
-        let mut current_height = current_height;
-        while on_the_way(current_height, target_height) {
-            Ticker::every(Duration::from_millis(10)).next().await;
-            current_height = *HEIGHT.lock().await;
+    GUI_MENU.signal(
+        PositionSaved {
+            position: pos_num,
+            height,
+            unit,
         }
+        .into(),
+    );
+
+    let undo = async {
+        inputs.wait_all_released().await;
+        inputs.wait_for_single_press().await == Button::Pos1
+    };
+    if let Either::Second(true) = select(Timer::after(UNDO_TIMEOUT), undo).await {
+        log::info!("restoring previous value of position {pos_num}");
+        CONFIGURATION
+            .lock()
+            .await
+            .update(|data| *f(data) = previous);
+    }
+}
+
+async fn drive_to_position(inputs: &mut Inputs, target_height: Millimeters) {
+    const ALLOWED_DELTA_IN_STANDSTILL: Millimeters = Millimeters::from_mm(2);
+    let Some(current_height) = *HEIGHT.lock().await else {
+        log::warn!("current height unknown, refusing to drive to position.");
+        return;
+    };
+    let direction = match current_height.cmp_fuzzy_eq(target_height, ALLOWED_DELTA_IN_STANDSTILL) {
+        Ordering::Equal => return,
+        Ordering::Less => Direction::Up,
+        Ordering::Greater => Direction::Down,
     };
-    DIRECTION.request(direction).await;
+
+    let soft_approach = CONFIGURATION.lock().await.get().soft_approach;
+    let approach = Approach::new(direction, target_height, soft_approach);
+    let motion = DIRECTION.request(direction).await;
+    STATISTICS.lock().await.update(|s| s.preset_moves += 1);
 
     inputs.wait_all_released().await;
-    select3(
-        check_height(),
+    let result = select3(
+        drive_approach(motion, approach),
         inputs.wait_for_single_press(),
-        refresh_gui(|| start_gui(direction)),
+        progress_gui(current_height, target_height),
     )
     .await;
+    if let Either3::First(Err(reason)) = result {
+        log::warn!("drive to position aborted: {reason:?}");
+        event_log::record(Event::DriveAborted(reason));
+        if reason == AbortReason::Collision {
+            back_off(direction).await;
+        }
+    }
+    DIRECTION.request(Direction::Stopped).await;
+}
+
+/// Briefly drives in the opposite direction after a collision to free the obstacle.
+async fn back_off(direction: Direction) {
+    const BACK_OFF_TIME: Duration = Duration::from_millis(500);
+
+    let opposite = match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Stopped | Direction::ResetDrive => return,
+    };
+    log::info!("backing off in direction {opposite} after collision");
+    let motion = DIRECTION.request(opposite).await;
+    if motion.switched().await.is_ok() {
+        Timer::after(BACK_OFF_TIME).await;
+    }
     DIRECTION.request(Direction::Stopped).await;
 }
 
-async fn start_gui(direction: Direction) {
+/// Requests the directions of `approach` until the desk is close to its target.
+async fn drive_approach(
+    mut motion: Motion,
+    mut approach: Approach,
+) -> core::result::Result<(), AbortReason> {
+    let mut ticker = Ticker::every(Duration::from_millis(10));
+    let mut requested = approach.direction();
+    // the full speed part only needs the height, the pulses are timed in the loop
+    let (target, delta) = approach.full_speed_until();
+    motion.reached(target, delta).await?;
+    loop {
+        let height = (*HEIGHT.lock().await).ok_or(AbortReason::HeightUnknown)?;
+        match approach.update(Instant::now(), height) {
+            Step::Done => return Ok(()),
+            Step::Drive(direction) if direction != requested => {
+                requested = direction;
+                motion = DIRECTION.request(direction).await;
+                if direction != Direction::Stopped {
+                    motion.switched().await?;
+                    approach.switched(Instant::now());
+                }
+            }
+            Step::Drive(_) => {}
+        }
+        if let Either::First(reason) = select(motion.aborted(), ticker.next()).await {
+            return Err(reason);
+        }
+    }
+}
+
+async fn start_gui() {
     let height = *HEIGHT.lock().await;
+    show_start(height, None).await;
+}
+
+/// Shows the progress of driving from `start` to `target` on the start screen.
+async fn progress_gui(start: Millimeters, target: Millimeters) {
+    let mut speed = SpeedEstimator::new();
+    loop {
+        let height = *HEIGHT.lock().await;
+        if let Some(height) = height {
+            speed.push(Instant::now(), height);
+        }
+        let progress = Progress {
+            start,
+            target,
+            speed: speed.speed().map(|speed| speed as u16),
+        };
+        show_start(height, Some(progress)).await;
+        Timer::after(Duration::from_millis(100)).await;
+    }
+}
+
+async fn show_start(height: Option<Millimeters>, progress: Option<Progress>) {
+    let state = DIRECTION.state().await;
+    let (locked, unit) = {
+        let mut conf = CONFIGURATION.lock().await;
+        let data = conf.get();
+        (data.locked, data.unit)
+    };
     GUI_MENU.signal(
         Start {
-            height: Some(height),
-            direction,
+            height,
+            state,
+            locked,
+            unit,
+            progress,
         }
         .into(),
     );
 }
 
 async fn wait_for_first_measurement() {
-    let timeout = Duration::from_secs(2);
-    let check_height = async {
-        fn not_updated(h: &Mutex<Millimeters>) -> bool {
-            h.try_lock().map_or(true, |guard| guard.is_zero())
-        }
-
-        while not_updated(&HEIGHT) {
-            Ticker::every(Duration::from_hz(10)).next().await;
-        }
-    };
-
-    select(Timer::after(timeout), check_height).await;
+    if HEIGHT.lock().await.is_none() {
+        HEIGHT_MEASURED.wait().await;
+    }
 }
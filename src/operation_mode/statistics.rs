@@ -0,0 +1,22 @@
+use crate::{
+    data::GUI_MENU,
+    gui::StatisticsScreen,
+    input::{Button, Inputs},
+    statistics::STATISTICS,
+};
+
+pub async fn run(inputs: &mut Inputs) {
+    loop {
+        log::info!("running statistics screen");
+
+        let statistics = STATISTICS.lock().await.get().clone();
+        GUI_MENU.signal(StatisticsScreen { statistics }.into());
+
+        inputs.wait_all_released().await;
+        match inputs.wait_for_single_press().await {
+            Button::Pos1 => return,
+            Button::Pos2 => STATISTICS.lock().await.get().dump(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{input::Inputs, unit::Unit};
+
+use super::choice::run_choice;
+
+pub async fn run(inputs: &mut Inputs) {
+    run_choice(
+        inputs,
+        "unit",
+        Unit::CHOICES,
+        |data| data.unit,
+        |data, unit| data.unit = unit,
+    )
+    .await;
+}
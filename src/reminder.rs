@@ -0,0 +1,229 @@
+use embassy_time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::data::Millimeters;
+
+/// A due reminder is repeated after this time until the desk is moved.
+const REPEAT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Posture {
+    Sitting,
+    Standing,
+}
+
+impl Posture {
+    /// Classifies `height` by whether it is closer to the sitting or the standing height.
+    pub fn of(height: Millimeters, sitting: Millimeters, standing: Millimeters) -> Self {
+        let midpoint = Millimeters::from_mm((sitting.as_mm() + standing.as_mm()) / 2);
+        if height < midpoint {
+            Posture::Sitting
+        } else {
+            Posture::Standing
+        }
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Posture::Sitting => Posture::Standing,
+            Posture::Standing => Posture::Sitting,
+        }
+    }
+}
+
+impl core::fmt::Display for Posture {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let string = match self {
+            Posture::Sitting => "sitting",
+            Posture::Standing => "standing",
+        };
+
+        f.write_str(string)
+    }
+}
+
+/// Stand for `standing_minutes` out of every `period_minutes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub standing_minutes: u8,
+    pub period_minutes: u8,
+}
+
+impl Schedule {
+    /// Schedules offered in the options, `None` disables reminders.
+    pub const CHOICES: [Option<Schedule>; 6] = [
+        None,
+        Some(Schedule::new(15, 60)),
+        Some(Schedule::new(20, 60)),
+        Some(Schedule::new(30, 60)),
+        Some(Schedule::new(10, 30)),
+        Some(Schedule::new(15, 30)),
+    ];
+
+    const fn new(standing_minutes: u8, period_minutes: u8) -> Self {
+        Self {
+            standing_minutes,
+            period_minutes,
+        }
+    }
+
+    /// How long the desk may stay in `posture` before a reminder is due.
+    fn limit(self, posture: Posture) -> Duration {
+        let minutes = match posture {
+            Posture::Sitting => self.period_minutes.saturating_sub(self.standing_minutes),
+            Posture::Standing => self.standing_minutes,
+        };
+        Duration::from_secs(u64::from(minutes) * 60)
+    }
+}
+
+impl core::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "stand {} of {}min",
+            self.standing_minutes, self.period_minutes
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reminder {
+    /// Posture the user should change to.
+    pub posture: Posture,
+    /// Time spent in the current posture.
+    pub elapsed: Duration,
+}
+
+/// Tracks how long the desk stays in the sitting and standing range and decides when to
+/// remind the user to change the posture.
+///
+/// Does not do any I/O, the caller passes in the current posture and time.
+#[derive(Debug, Clone)]
+pub struct ReminderTracker {
+    current: Option<(Posture, Instant)>,
+    reminded_at: Option<Instant>,
+}
+
+impl ReminderTracker {
+    pub const fn new() -> Self {
+        Self {
+            current: None,
+            reminded_at: None,
+        }
+    }
+
+    /// Updates the tracker with the current posture, `None` while it is unknown.
+    ///
+    /// Returns a reminder if one is due according to `schedule`.
+    pub fn update(
+        &mut self,
+        now: Instant,
+        posture: Option<Posture>,
+        schedule: Option<Schedule>,
+    ) -> Option<Reminder> {
+        if posture != self.current.map(|(posture, _)| posture) {
+            self.current = posture.map(|posture| (posture, now));
+            self.reminded_at = None;
+        }
+
+        let (posture, since) = self.current?;
+        let elapsed = now - since;
+        let repeat_due = self
+            .reminded_at
+            .map_or(true, |reminded_at| now - reminded_at >= REPEAT_INTERVAL);
+        if elapsed < schedule?.limit(posture) || !repeat_due {
+            return None;
+        }
+
+        self.reminded_at = Some(now);
+        Some(Reminder {
+            posture: posture.other(),
+            elapsed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: u64) -> Instant {
+        Instant::from_secs(minutes * 60)
+    }
+
+    const SCHEDULE: Option<Schedule> = Some(Schedule::new(15, 60));
+
+    fn reminder(posture: Posture, elapsed_minutes: u64) -> Option<Reminder> {
+        Some(Reminder {
+            posture,
+            elapsed: Duration::from_secs(elapsed_minutes * 60),
+        })
+    }
+
+    #[test]
+    fn posture_by_midpoint() {
+        let (sitting, standing) = (Millimeters::from_mm(700), Millimeters::from_mm(1100));
+        let of = |height| Posture::of(Millimeters::from_mm(height), sitting, standing);
+        assert_eq!(of(650), Posture::Sitting);
+        assert_eq!(of(899), Posture::Sitting);
+        assert_eq!(of(900), Posture::Standing);
+        assert_eq!(of(1200), Posture::Standing);
+    }
+
+    #[test]
+    fn reminds_after_limit_and_repeats() {
+        let mut tracker = ReminderTracker::new();
+        let sitting = Some(Posture::Sitting);
+        assert_eq!(tracker.update(at(0), sitting, SCHEDULE), None);
+        assert_eq!(tracker.update(at(44), sitting, SCHEDULE), None);
+        assert_eq!(
+            tracker.update(at(45), sitting, SCHEDULE),
+            reminder(Posture::Standing, 45)
+        );
+        // not again until the repeat interval passed
+        assert_eq!(tracker.update(at(46), sitting, SCHEDULE), None);
+        assert_eq!(tracker.update(at(49), sitting, SCHEDULE), None);
+        assert_eq!(
+            tracker.update(at(50), sitting, SCHEDULE),
+            reminder(Posture::Standing, 50)
+        );
+    }
+
+    #[test]
+    fn switching_posture_restarts_the_time() {
+        let mut tracker = ReminderTracker::new();
+        let sitting = Some(Posture::Sitting);
+        assert_eq!(tracker.update(at(0), sitting, SCHEDULE), None);
+        assert!(tracker.update(at(45), sitting, SCHEDULE).is_some());
+
+        let standing = Some(Posture::Standing);
+        assert_eq!(tracker.update(at(46), standing, SCHEDULE), None);
+        assert_eq!(tracker.update(at(60), standing, SCHEDULE), None);
+        assert_eq!(
+            tracker.update(at(61), standing, SCHEDULE),
+            reminder(Posture::Sitting, 15)
+        );
+    }
+
+    #[test]
+    fn unknown_posture_restarts_the_time() {
+        let mut tracker = ReminderTracker::new();
+        let sitting = Some(Posture::Sitting);
+        assert_eq!(tracker.update(at(0), sitting, SCHEDULE), None);
+        assert_eq!(tracker.update(at(30), None, SCHEDULE), None);
+        assert_eq!(tracker.update(at(31), sitting, SCHEDULE), None);
+        assert_eq!(tracker.update(at(75), sitting, SCHEDULE), None);
+        assert_eq!(
+            tracker.update(at(76), sitting, SCHEDULE),
+            reminder(Posture::Standing, 45)
+        );
+    }
+
+    #[test]
+    fn disabled_schedule_never_reminds() {
+        let mut tracker = ReminderTracker::new();
+        assert_eq!(tracker.update(at(0), Some(Posture::Standing), None), None);
+        assert_eq!(tracker.update(at(600), Some(Posture::Standing), None), None);
+    }
+}
@@ -0,0 +1,162 @@
+use embassy_time::{Duration, Instant};
+
+use crate::data::Millimeters;
+
+/// The reset drive is stopped if the desk did not settle within this time.
+pub const TIMEOUT: Duration = Duration::from_secs(90);
+/// The desk is considered settled once the height did not change for this long.
+const SETTLE_TIME: Duration = Duration::from_secs(3);
+/// Height changes below this are measurement noise.
+const NOISE: Millimeters = Millimeters::from_mm(3);
+/// The desk must move at least this far before it can be considered settled.
+const MIN_TRAVEL: Millimeters = Millimeters::from_mm(10);
+/// A desk that starts at its lower limit is already re-homed if it did not move for this long.
+const AT_LOWER_LIMIT_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The controller did not start moving the desk yet.
+    Waiting,
+    Moving,
+    /// The desk stopped, but not for long enough yet.
+    Settling,
+    /// The desk reached the bottom and stopped.
+    Finished,
+    TimedOut,
+}
+
+impl core::fmt::Display for Phase {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let string = match self {
+            Phase::Waiting => "waiting for desk",
+            Phase::Moving => "moving",
+            Phase::Settling => "settling",
+            Phase::Finished => "finished",
+            Phase::TimedOut => "timed out",
+        };
+        f.write_str(string)
+    }
+}
+
+/// Watches the height during a reset drive to detect when the desk finished re-homing.
+#[derive(Debug, Clone)]
+pub struct ResetDriveMonitor {
+    started: Instant,
+    /// Lowest calibrated height, if known.
+    lower_limit: Option<Millimeters>,
+    start_height: Option<Millimeters>,
+    /// Last height that differed by more than [`NOISE`] and when it was measured.
+    last_change: Option<(Millimeters, Instant)>,
+    lowest: Option<Millimeters>,
+    moved: bool,
+}
+
+impl ResetDriveMonitor {
+    pub fn new(now: Instant, lower_limit: Option<Millimeters>) -> Self {
+        Self {
+            started: now,
+            lower_limit,
+            start_height: None,
+            last_change: None,
+            lowest: None,
+            moved: false,
+        }
+    }
+
+    pub fn update(&mut self, now: Instant, height: Option<Millimeters>) -> Phase {
+        if now - self.started > TIMEOUT {
+            return Phase::TimedOut;
+        }
+        let Some(height) = height else {
+            return self.phase(now);
+        };
+
+        let start_height = *self.start_height.get_or_insert(height);
+        self.moved |= height.cmp_fuzzy_eq(start_height, MIN_TRAVEL).is_ne();
+        self.lowest = Some(self.lowest.map_or(height, |lowest| lowest.min(height)));
+        match self.last_change {
+            Some((last, _)) if last.cmp_fuzzy_eq(height, NOISE).is_eq() => {}
+            _ => self.last_change = Some((height, now)),
+        }
+
+        self.phase(now)
+    }
+
+    fn phase(&self, now: Instant) -> Phase {
+        match self.last_change {
+            Some((_, since))
+                if !self.moved
+                    && self.started_at_lower_limit()
+                    && now - since >= AT_LOWER_LIMIT_TIME =>
+            {
+                Phase::Finished
+            }
+            _ if !self.moved => Phase::Waiting,
+            Some((_, since)) if now - since >= SETTLE_TIME => Phase::Finished,
+            Some((_, since)) if now - since >= SETTLE_TIME / 6 => Phase::Settling,
+            _ => Phase::Moving,
+        }
+    }
+
+    fn started_at_lower_limit(&self) -> bool {
+        self.lower_limit
+            .zip(self.start_height)
+            .is_some_and(|(limit, start)| start.cmp_fuzzy_eq(limit, MIN_TRAVEL).is_le())
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now - self.started
+    }
+
+    /// Lowest height seen during the reset drive.
+    pub fn lowest(&self) -> Option<Millimeters> {
+        self.lowest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn mm(value: u16) -> Option<Millimeters> {
+        Some(Millimeters::from_mm(value))
+    }
+
+    #[test]
+    fn finishes_after_moving_down() {
+        let mut monitor = ResetDriveMonitor::new(at(0), mm(650));
+        assert_eq!(monitor.update(at(0), mm(900)), Phase::Waiting);
+        assert_eq!(monitor.update(at(1000), mm(800)), Phase::Moving);
+        assert_eq!(monitor.update(at(2000), mm(640)), Phase::Moving);
+        assert_eq!(monitor.update(at(3000), mm(641)), Phase::Settling);
+        assert_eq!(monitor.update(at(5000), mm(640)), Phase::Finished);
+        assert_eq!(monitor.lowest(), mm(640));
+    }
+
+    #[test]
+    fn finishes_when_already_at_lower_limit() {
+        let mut monitor = ResetDriveMonitor::new(at(0), mm(650));
+        assert_eq!(monitor.update(at(0), mm(652)), Phase::Waiting);
+        assert_eq!(monitor.update(at(9000), mm(651)), Phase::Waiting);
+        assert_eq!(monitor.update(at(10_000), mm(652)), Phase::Finished);
+    }
+
+    #[test]
+    fn waits_for_movement_above_lower_limit() {
+        let mut monitor = ResetDriveMonitor::new(at(0), mm(650));
+        monitor.update(at(0), mm(900));
+        assert_eq!(monitor.update(at(60_000), mm(900)), Phase::Waiting);
+        assert_eq!(monitor.update(at(91_000), mm(900)), Phase::TimedOut);
+    }
+
+    #[test]
+    fn unknown_lower_limit_waits_for_movement() {
+        let mut monitor = ResetDriveMonitor::new(at(0), None);
+        monitor.update(at(0), mm(650));
+        assert_eq!(monitor.update(at(60_000), mm(650)), Phase::Waiting);
+    }
+}
@@ -0,0 +1,107 @@
+use heapless::HistoryBuffer;
+
+/// Keeps the last `N` ADC samples and computes their median.
+///
+/// New samples replace the oldest ones, so the median can be recomputed after every sample
+/// instead of collecting a full batch first.
+pub struct SlidingMedian<const N: usize> {
+    window: HistoryBuffer<u16, N>,
+}
+
+impl<const N: usize> SlidingMedian<N> {
+    pub const fn new() -> Self {
+        Self {
+            window: HistoryBuffer::new(),
+        }
+    }
+
+    pub fn push(&mut self, sample: u16) {
+        self.window.write(sample);
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.window.len() == N
+    }
+
+    /// Median of the samples in the window, `None` if no sample was pushed yet.
+    pub fn median(&self) -> Option<u16> {
+        if self.window.is_empty() {
+            return None;
+        }
+
+        let mut samples = [0; N];
+        let samples = &mut samples[..self.window.len()];
+        samples.copy_from_slice(self.window.as_slice());
+        Some(compute_median(samples))
+    }
+}
+
+fn compute_median(samples: &mut [u16]) -> u16 {
+    samples.sort_unstable();
+    let len = samples.len();
+    if len % 2 == 0 {
+        let right_mid = samples[len / 2];
+        let left_mid = samples[(len / 2) - 1];
+        // sorted, so this cannot overflow
+        left_mid + (right_mid - left_mid) / 2
+    } else {
+        samples[len / 2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn median<const N: usize>(samples: &[u16]) -> Option<u16> {
+        let mut window = SlidingMedian::<N>::new();
+        for &sample in samples {
+            window.push(sample);
+        }
+        window.median()
+    }
+
+    #[test]
+    fn odd_window() {
+        assert_eq!(median::<5>(&[]), None);
+        assert_eq!(median::<5>(&[30, 10, 50, 20, 40]), Some(30));
+        // only the last five samples count
+        assert_eq!(median::<5>(&[1000, 1000, 30, 10, 50, 20, 40]), Some(30));
+    }
+
+    #[test]
+    fn even_window() {
+        assert_eq!(median::<4>(&[40, 10, 30, 20]), Some(25));
+        // the sum of the middle samples does not fit into a u16
+        assert_eq!(
+            median::<4>(&[u16::MAX, u16::MAX - 2, u16::MAX, 7]),
+            Some(u16::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn partially_filled_window() {
+        let mut window = SlidingMedian::<4>::new();
+        window.push(10);
+        assert!(!window.is_full());
+        assert_eq!(window.median(), Some(10));
+        window.push(30);
+        window.push(20);
+        assert_eq!(window.median(), Some(20));
+        window.push(40);
+        assert!(window.is_full());
+    }
+
+    #[test]
+    fn single_spike_is_rejected() {
+        let mut samples = [2000; 5];
+        for spike in [0, 4095] {
+            for position in 0..samples.len() {
+                samples[position] = spike;
+                assert_eq!(median::<5>(&samples), Some(2000));
+                samples[position] = 2000;
+            }
+        }
+        assert_eq!(median::<4>(&[2000, 2010, 4095, 2020]), Some(2015));
+    }
+}
@@ -0,0 +1,258 @@
+//! The photo couplers emulate the buttons of the original controller, so the motor speed
+//! cannot be controlled with PWM. Instead, the desk approaches the target in short pulses.
+//!
+//! Every pulse is shorter than the startup time the collision detection waits for the motors
+//! to ramp up, so collisions are not detected during the pulsed part of an approach. This is
+//! limited to the last [`SoftApproach::distance`] at a fraction of the full speed.
+
+use embassy_time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Direction, Millimeters};
+
+/// The desk coasts a little after the outputs are switched off at full speed.
+const ALLOWED_DELTA_IN_MOVEMENT: Millimeters = Millimeters::from_mm(18);
+const ALLOWED_DELTA_WHILE_PULSING: Millimeters = Millimeters::from_mm(4);
+
+/// Pulse the outputs within `distance_mm` of the target of a drive to a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoftApproach {
+    pub distance_mm: u16,
+    pub on_ms: u16,
+    pub off_ms: u16,
+}
+
+impl SoftApproach {
+    /// Approaches offered in the options, `None` drives to the target at full speed.
+    pub const CHOICES: [Option<SoftApproach>; 5] = [
+        None,
+        Some(SoftApproach::new(30, 200, 100)),
+        Some(SoftApproach::new(50, 150, 150)),
+        Some(SoftApproach::new(80, 150, 150)),
+        Some(SoftApproach::new(80, 100, 200)),
+    ];
+
+    const fn new(distance_mm: u16, on_ms: u16, off_ms: u16) -> Self {
+        Self {
+            distance_mm,
+            on_ms,
+            off_ms,
+        }
+    }
+
+    pub fn distance(self) -> Millimeters {
+        Millimeters::from_mm(self.distance_mm)
+    }
+
+    pub fn on(self) -> Duration {
+        Duration::from_millis(self.on_ms.into())
+    }
+
+    pub fn off(self) -> Duration {
+        Duration::from_millis(self.off_ms.into())
+    }
+}
+
+impl core::fmt::Display for SoftApproach {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}mm {}/{}ms", self.distance_mm, self.on_ms, self.off_ms)
+    }
+}
+
+/// What the outputs should do next, see [`Approach::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Drive in this direction, [`Direction::Stopped`] pauses between pulses.
+    Drive(Direction),
+    /// The desk is close enough to the target.
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    FullSpeed,
+    Paused {
+        since: Instant,
+    },
+    /// The pulse starts once the outputs were switched.
+    Pulse {
+        since: Option<Instant>,
+    },
+    Done,
+}
+
+/// Decides how to drive towards a target, at full speed and then in pulses if configured.
+///
+/// Does not do any I/O, the caller requests the direction of every [`Step`] and reports when
+/// the outputs were switched.
+#[derive(Debug, Clone)]
+pub struct Approach {
+    direction: Direction,
+    target: Millimeters,
+    soft_approach: Option<SoftApproach>,
+    phase: Phase,
+}
+
+impl Approach {
+    pub fn new(
+        direction: Direction,
+        target: Millimeters,
+        soft_approach: Option<SoftApproach>,
+    ) -> Self {
+        Self {
+            direction,
+            target,
+            soft_approach,
+            phase: Phase::FullSpeed,
+        }
+    }
+
+    /// Direction the desk is initially driven in.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Must be called once the outputs were switched for a [`Step::Drive`] that moves the desk.
+    pub fn switched(&mut self, now: Instant) {
+        if let Phase::Pulse { since: None } = self.phase {
+            self.phase = Phase::Pulse { since: Some(now) };
+        }
+    }
+
+    pub fn update(&mut self, now: Instant, height: Millimeters) -> Step {
+        match (self.phase, self.soft_approach) {
+            (Phase::FullSpeed, None) if self.reached(height, ALLOWED_DELTA_IN_MOVEMENT) => {
+                self.phase = Phase::Done;
+            }
+            (Phase::FullSpeed, Some(soft_approach))
+                if self.reached(height, soft_approach.distance()) =>
+            {
+                log::debug!("approaching target in pulses of {soft_approach}");
+                self.phase = Phase::Paused { since: now };
+            }
+            (Phase::Pulse { .. }, _) if self.reached(height, ALLOWED_DELTA_WHILE_PULSING) => {
+                self.phase = Phase::Done;
+            }
+            (Phase::Paused { since }, Some(soft_approach))
+                if now - since >= soft_approach.off() =>
+            {
+                self.phase = Phase::Pulse { since: None };
+            }
+            (Phase::Pulse { since: Some(since) }, Some(soft_approach))
+                if now - since >= soft_approach.on() =>
+            {
+                self.phase = Phase::Paused { since: now };
+            }
+            _ => {}
+        }
+
+        match self.phase {
+            Phase::FullSpeed | Phase::Pulse { .. } => Step::Drive(self.direction),
+            Phase::Paused { .. } => Step::Drive(Direction::Stopped),
+            Phase::Done => Step::Done,
+        }
+    }
+
+    /// Target and distance to it at which the desk stops driving at full speed.
+    pub fn full_speed_until(&self) -> (Millimeters, Millimeters) {
+        let delta = self
+            .soft_approach
+            .map_or(ALLOWED_DELTA_IN_MOVEMENT, SoftApproach::distance);
+        (self.target, delta)
+    }
+
+    fn reached(&self, height: Millimeters, delta: Millimeters) -> bool {
+        self.direction.reached(height, self.target, delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn mm(value: u16) -> Millimeters {
+        Millimeters::from_mm(value)
+    }
+
+    #[test]
+    fn full_speed() {
+        let mut approach = Approach::new(Direction::Up, mm(1100), None);
+        assert_eq!(approach.update(at(0), mm(700)), Step::Drive(Direction::Up));
+        assert_eq!(
+            approach.update(at(100), mm(1080)),
+            Step::Drive(Direction::Up)
+        );
+        assert_eq!(approach.update(at(200), mm(1083)), Step::Done);
+        assert_eq!(approach.update(at(300), mm(1090)), Step::Done);
+    }
+
+    #[test]
+    fn pulses() {
+        let soft_approach = SoftApproach::new(50, 150, 100);
+        let mut approach = Approach::new(Direction::Down, mm(700), Some(soft_approach));
+        assert_eq!(
+            approach.update(at(0), mm(1000)),
+            Step::Drive(Direction::Down)
+        );
+        assert_eq!(
+            approach.update(at(1000), mm(749)),
+            Step::Drive(Direction::Stopped)
+        );
+        assert_eq!(
+            approach.update(at(1099), mm(745)),
+            Step::Drive(Direction::Stopped)
+        );
+        assert_eq!(
+            approach.update(at(1100), mm(745)),
+            Step::Drive(Direction::Down)
+        );
+        // the pulse only starts once the outputs were switched
+        assert_eq!(
+            approach.update(at(1300), mm(745)),
+            Step::Drive(Direction::Down)
+        );
+        approach.switched(at(1300));
+        assert_eq!(
+            approach.update(at(1449), mm(730)),
+            Step::Drive(Direction::Down)
+        );
+        assert_eq!(
+            approach.update(at(1450), mm(728)),
+            Step::Drive(Direction::Stopped)
+        );
+        assert_eq!(
+            approach.update(at(1550), mm(726)),
+            Step::Drive(Direction::Down)
+        );
+        approach.switched(at(1560));
+        assert_eq!(approach.update(at(1600), mm(703)), Step::Done);
+    }
+
+    #[test]
+    fn overshoot_while_pulsing() {
+        let soft_approach = SoftApproach::new(30, 200, 100);
+        let mut approach = Approach::new(Direction::Up, mm(1100), Some(soft_approach));
+        approach.update(at(0), mm(1075));
+        approach.update(at(100), mm(1075));
+        approach.switched(at(110));
+        assert_eq!(approach.update(at(200), mm(1120)), Step::Done);
+    }
+
+    #[test]
+    fn already_close() {
+        let soft_approach = SoftApproach::new(80, 100, 200);
+        let mut approach = Approach::new(Direction::Up, mm(1100), Some(soft_approach));
+        assert_eq!(
+            approach.update(at(0), mm(1050)),
+            Step::Drive(Direction::Stopped)
+        );
+        assert_eq!(
+            approach.update(at(200), mm(1050)),
+            Step::Drive(Direction::Up)
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use embassy_time::{Duration, Instant};
+use heapless::Deque;
+
+use crate::data::Millimeters;
+
+/// Speed is measured over this window to smooth out the measurement noise.
+const WINDOW: Duration = Duration::from_millis(500);
+/// Enough samples for [`WINDOW`] if they are at least 100ms apart.
+const MAX_SAMPLES: usize = 8;
+
+/// Estimates the speed of the desk from successive height readings.
+#[derive(Debug, Clone)]
+pub struct SpeedEstimator {
+    samples: Deque<(Instant, Millimeters), MAX_SAMPLES>,
+}
+
+impl SpeedEstimator {
+    pub const fn new() -> Self {
+        Self {
+            samples: Deque::new(),
+        }
+    }
+
+    pub fn push(&mut self, now: Instant, height: Millimeters) {
+        if self.samples.is_full() {
+            self.samples.pop_front();
+        }
+        let _ = self.samples.push_back((now, height));
+    }
+
+    /// Speed in mm/s, regardless of the direction, or `None` if there are not enough samples.
+    pub fn speed(&self) -> Option<f32> {
+        let &(now, newest) = self.samples.back()?;
+        let &(then, oldest) = self
+            .samples
+            .iter()
+            .rev()
+            .find(|(time, _)| now - *time >= WINDOW)?;
+        let distance = f32::from(newest.as_mm().abs_diff(oldest.as_mm()));
+        let elapsed = (now - then).as_millis() as f32 / 1000.0;
+        Some(distance / elapsed)
+    }
+}
@@ -0,0 +1,140 @@
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use embassy_time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{data::Direction, reminder::Posture, storage::FlashRecord};
+
+pub static STATISTICS: Mutex<CriticalSectionRawMutex, StatisticsData> =
+    Mutex::new(StatisticsData::const_default());
+
+/// Sector after the configuration, so that frequent statistics updates do not put the
+/// configuration at risk. The magic must be changed whenever the layout of [`Statistics`]
+/// changes.
+const RECORD: FlashRecord = FlashRecord::new("statistics", 0xA000, [83, 84, 65, 1]);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Statistics {
+    pub sitting_secs: u32,
+    pub standing_secs: u32,
+    pub manual_moves: u32,
+    pub preset_moves: u32,
+    pub motor_up_ms: u64,
+    pub motor_down_ms: u64,
+}
+
+impl Statistics {
+    const fn const_default() -> Self {
+        Self {
+            sitting_secs: 0,
+            standing_secs: 0,
+            manual_moves: 0,
+            preset_moves: 0,
+            motor_up_ms: 0,
+            motor_down_ms: 0,
+        }
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.manual_moves + self.preset_moves
+    }
+
+    pub fn add_posture_time(&mut self, posture: Posture, duration: Duration) {
+        let secs = u32::try_from(duration.as_secs()).unwrap_or(u32::MAX);
+        let counter = match posture {
+            Posture::Sitting => &mut self.sitting_secs,
+            Posture::Standing => &mut self.standing_secs,
+        };
+        *counter = counter.saturating_add(secs);
+    }
+
+    pub fn add_motor_runtime(&mut self, direction: Direction, duration: Duration) {
+        let counter = match direction {
+            Direction::Up => &mut self.motor_up_ms,
+            Direction::Down => &mut self.motor_down_ms,
+            Direction::Stopped | Direction::ResetDrive => return,
+        };
+        *counter = counter.saturating_add(duration.as_millis());
+    }
+
+    /// Writes the statistics to the log, i.e. the serial port.
+    pub fn dump(&self) {
+        log::info!(
+            "usage statistics:\n\
+             sitting: {}min\n\
+             standing: {}min\n\
+             moves: {} ({} preset, {} manual)\n\
+             motor runtime up: {}s\n\
+             motor runtime down: {}s",
+            self.sitting_secs / 60,
+            self.standing_secs / 60,
+            self.moves(),
+            self.preset_moves,
+            self.manual_moves,
+            self.motor_up_ms / 1000,
+            self.motor_down_ms / 1000,
+        );
+    }
+}
+
+/// Statistics change all the time, so unlike the configuration they are only written to
+/// flash when [`StatisticsData::persist`] is called.
+#[derive(Clone, Debug)]
+pub struct StatisticsData {
+    loaded: bool,
+    inner: Statistics,
+    changed: bool,
+}
+
+impl StatisticsData {
+    pub const fn const_default() -> Self {
+        Self {
+            loaded: false,
+            inner: Statistics::const_default(),
+            changed: false,
+        }
+    }
+
+    pub fn get(&mut self) -> &Statistics {
+        self.init_inner();
+        &self.inner
+    }
+
+    pub fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Statistics),
+    {
+        self.init_inner();
+        f(&mut self.inner);
+        self.changed = true;
+    }
+
+    /// Writes the statistics to flash if they changed since the last call.
+    pub fn persist(&mut self) {
+        if !self.changed {
+            return;
+        }
+        self.store();
+        self.changed = false;
+    }
+
+    fn init_inner(&mut self) {
+        if self.loaded {
+            return;
+        }
+
+        let mut buffer = [0; FlashRecord::buffer_size::<Statistics>()];
+        self.inner = RECORD
+            .load(&mut buffer)
+            .unwrap_or_else(|_| Statistics::const_default());
+        self.loaded = true;
+
+        log::info!("Initialized statistics: {self:#?}");
+    }
+
+    fn store(&self) {
+        RECORD.store(
+            &self.inner,
+            &mut [0; FlashRecord::buffer_size::<Statistics>()],
+        );
+    }
+}
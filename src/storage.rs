@@ -1,21 +1,157 @@
+#[cfg(not(target_arch = "xtensa"))]
+mod host_flash;
+
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
 use embedded_storage::{ReadStorage, Storage};
+#[cfg(target_arch = "xtensa")]
 use esp_storage::FlashStorage;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 
-use crate::data::{Calibration, Millimeters};
+#[cfg(not(target_arch = "xtensa"))]
+use self::host_flash::FlashStorage;
+use crate::{
+    action::ActionMap,
+    collision::Sensitivity,
+    data::{Calibration, Millimeters},
+    display_power::DisplayTimeout,
+    event_log::{self, Event, LoadError},
+    input::LongPress,
+    reminder::Schedule,
+    soft_approach::SoftApproach,
+    unit::Unit,
+};
 
 pub static CONFIGURATION: Mutex<CriticalSectionRawMutex, StorageData> =
     Mutex::new(StorageData::const_default());
 
-const MAGIC_BYTES: [u8; 4] = [123, 52, 61, 53];
-const FLASH_ADDR: u32 = 0x9000;
+/// The last magic byte is the version of the layout of [`InnerData`], see [`LAYOUTS`].
+const RECORD: FlashRecord = FlashRecord::new("configuration", 0x9000, [123, 52, 61, 61]);
+
+/// Number of [`InnerData`] fields stored by each layout version. Fields are only ever
+/// appended, so older configurations are migrated by keeping the defaults of the fields
+/// they lack. A new version must be added whenever a field is appended.
+const LAYOUTS: [(u8, usize); 9] = [
+    (53, 3),
+    (54, 4),
+    (55, 5),
+    (56, 6),
+    (57, 7),
+    (58, 8),
+    (59, 9),
+    (60, 10),
+    (61, 12),
+];
+
+/// Length of the magic identifier in front of every [`FlashRecord`].
+const MAGIC_LEN: usize = 4;
+
+/// Data that is stored at a fixed flash address behind a magic identifier.
+pub struct FlashRecord {
+    name: &'static str,
+    address: u32,
+    magic: [u8; MAGIC_LEN],
+}
+
+impl FlashRecord {
+    pub const fn new(name: &'static str, address: u32, magic: [u8; MAGIC_LEN]) -> Self {
+        Self {
+            name,
+            address,
+            magic,
+        }
+    }
+
+    const fn version(&self) -> u8 {
+        self.magic[MAGIC_LEN - 1]
+    }
+
+    /// Size of the buffer needed to load or store `T`.
+    pub const fn buffer_size<T>() -> usize {
+        MAGIC_LEN + core::mem::size_of::<T>()
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, buffer: &mut [u8]) -> Result<T, LoadError> {
+        self.load_with(buffer, |version, data| {
+            if version != self.version() {
+                return None;
+            }
+            Some(postcard::from_bytes(data))
+        })
+    }
+
+    /// Loads data that may have been stored with an older layout version. `deserialize`
+    /// gets the version and the serialized data and returns `None` for unknown versions.
+    pub fn load_with<T>(
+        &self,
+        buffer: &mut [u8],
+        deserialize: impl FnOnce(u8, &[u8]) -> Option<postcard::Result<T>>,
+    ) -> Result<T, LoadError> {
+        FlashStorage::new()
+            .read(self.address, buffer)
+            .map_err(|e| {
+                log::error!("failed to read {} from flash: {e:?}", self.name);
+                LoadError::Read
+            })?;
+
+        let (magic, data) = buffer.split_at(MAGIC_LEN);
+        let known = magic[..MAGIC_LEN - 1] == self.magic[..MAGIC_LEN - 1];
+        let Some(result) = known
+            .then(|| deserialize(magic[MAGIC_LEN - 1], data))
+            .flatten()
+        else {
+            log::warn!(
+                "invalid magic identifier {magic:?}, ignoring {}.\nThis is normal during first-time use.",
+                self.name
+            );
+            return Err(LoadError::MagicMismatch);
+        };
+
+        result.map_err(|e| {
+            log::error!("failed to load {}: {e}", self.name);
+            LoadError::Deserialize
+        })
+    }
+
+    pub fn store<T: Serialize>(&self, value: &T, buffer: &mut [u8]) {
+        let (magic, data) = buffer.split_at_mut(MAGIC_LEN);
+        magic.copy_from_slice(&self.magic);
+        let Ok(length) = postcard::to_slice(value, data)
+            .map(|data| MAGIC_LEN + data.len())
+            .inspect_err(|e| log::error!("failed to serialize {}: {e}", self.name))
+        else {
+            return;
+        };
+
+        log::info!("saving {length} bytes of {} to flash storage", self.name);
+        let _ = FlashStorage::new()
+            .write(self.address, &buffer[..length])
+            .inspect_err(|e| log::error!("failed to write {} to flash: {e:?}", self.name));
+    }
+}
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct InnerData {
     pub position_1: Option<Millimeters>,
     pub position_2: Option<Millimeters>,
     pub calibration: Calibration,
+    pub long_press: LongPress,
+    pub actions: ActionMap,
+    /// Ignore all buttons except for the ones bound to [`crate::action::Action::ToggleLock`].
+    pub locked: bool,
+    /// Sit/stand reminders are disabled if `None`.
+    pub reminder: Option<Schedule>,
+    /// Drive to positions at full speed if `None`.
+    pub soft_approach: Option<SoftApproach>,
+    /// Collision detection is disabled if `None`.
+    pub collision_sensitivity: Option<Sensitivity>,
+    pub unit: Unit,
+    /// The display stays on if `None`.
+    pub display_timeout: Option<DisplayTimeout>,
+    /// Move the content of the start screen a little from time to time to avoid burn-in.
+    pub pixel_shift: bool,
 }
 
 impl InnerData {
@@ -24,20 +160,83 @@ impl InnerData {
             position_1: None,
             position_2: None,
             calibration: Calibration::new(),
+            long_press: LongPress::DEFAULT,
+            actions: ActionMap::const_default(),
+            locked: false,
+            reminder: None,
+            soft_approach: None,
+            collision_sensitivity: Sensitivity::CHOICES[1],
+            unit: Unit::Centimeters,
+            display_timeout: DisplayTimeout::CHOICES[3],
+            pixel_shift: true,
+        }
+    }
+
+    /// Deserializes the configuration stored with layout `version`.
+    fn from_bytes(version: u8, bytes: &[u8]) -> Option<postcard::Result<Self>> {
+        let &(_, fields) = LAYOUTS.iter().find(|(v, _)| *v == version)?;
+        if version != RECORD.version() {
+            log::info!("migrating configuration from layout version {version}");
         }
+        let mut deserializer = postcard::Deserializer::from_bytes(bytes);
+        Some(deserializer.deserialize_tuple(fields, InnerDataVisitor))
+    }
+
+    /// Sitting and standing height, i.e. the lower and the higher of both positions.
+    pub fn sit_stand_heights(&self) -> Option<(Millimeters, Millimeters)> {
+        let (position_1, position_2) = (self.position_1?, self.position_2?);
+        Some((position_1.min(position_2), position_1.max(position_2)))
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Reads the fields of [`InnerData`] in order as long as there are any.
+struct InnerDataVisitor;
+
+impl<'de> Visitor<'de> for InnerDataVisitor {
+    type Value = InnerData;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("configuration")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        fn next<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(
+            seq: &mut A,
+            field: &mut T,
+        ) -> Result<(), A::Error> {
+            if let Some(value) = seq.next_element()? {
+                *field = value;
+            }
+            Ok(())
+        }
+
+        let mut data = InnerData::const_default();
+        next(&mut seq, &mut data.position_1)?;
+        next(&mut seq, &mut data.position_2)?;
+        next(&mut seq, &mut data.calibration)?;
+        next(&mut seq, &mut data.long_press)?;
+        next(&mut seq, &mut data.actions)?;
+        next(&mut seq, &mut data.locked)?;
+        next(&mut seq, &mut data.reminder)?;
+        next(&mut seq, &mut data.soft_approach)?;
+        next(&mut seq, &mut data.collision_sensitivity)?;
+        next(&mut seq, &mut data.unit)?;
+        next(&mut seq, &mut data.display_timeout)?;
+        next(&mut seq, &mut data.pixel_shift)?;
+        Ok(data)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct StorageData {
-    magic_identifier: [u8; 4],
+    loaded: bool,
     inner: InnerData,
 }
 
 impl StorageData {
     pub const fn const_default() -> Self {
         Self {
-            magic_identifier: [0; 4],
+            loaded: false,
             inner: InnerData::const_default(),
         }
     }
@@ -48,51 +247,26 @@ impl StorageData {
     }
 
     fn init_inner(&mut self) {
-        if self.magic_identifier == MAGIC_BYTES {
+        if self.loaded {
             return;
         }
 
-        *self = Self::load().unwrap_or_else(Self::const_default);
-        self.magic_identifier = MAGIC_BYTES;
+        let mut buffer = [0; FlashRecord::buffer_size::<InnerData>()];
+        self.inner = RECORD
+            .load_with(&mut buffer, InnerData::from_bytes)
+            .inspect_err(|&e| event_log::record(Event::ConfigurationLoadFailed(e)))
+            .unwrap_or_else(|_| InnerData::const_default());
+        self.loaded = true;
 
         log::info!("Initialized storage data: {self:#?}");
     }
 
-    fn load() -> Option<Self> {
-        let mut bytes = [0u8; core::mem::size_of::<StorageData>()];
-        FlashStorage::new()
-            .read(FLASH_ADDR, &mut bytes)
-            .inspect_err(|e| log::error!("failed to read flash storage: {e:?}"))
-            .ok()?;
-
-        let this: Self = postcard::from_bytes(&bytes)
-            .inspect_err(|e| {
-                log::error!(
-                    "failed to load configuration: {e}\nThis is normal during first-time use."
-                )
-            })
-            .ok()?;
-
-        if this.magic_identifier != MAGIC_BYTES {
-            log::error!("invalid magic identifier {:?}, ignoring configuration.\nThis is normal during first-time use.", this.magic_identifier);
-            return None;
-        }
-
-        Some(this)
-    }
-
     fn store(&self) {
         log::debug!("serializing data for flash storage: {:?}", self);
-
-        const DATA_SIZE: usize = core::mem::size_of::<StorageData>();
-        let Ok(bytes) = postcard::to_vec::<_, DATA_SIZE>(self)
-            .inspect_err(|e| log::error!("failed to serialize configuration: {e}"))
-            else {return;};
-
-        log::info!("saving {} bytes to flash storage", bytes.len());
-        let _ = FlashStorage::new()
-            .write(FLASH_ADDR, &bytes)
-            .inspect_err(|e| log::error!("failed to write flash storage: {e:?}"));
+        RECORD.store(
+            &self.inner,
+            &mut [0; FlashRecord::buffer_size::<InnerData>()],
+        );
     }
 
     pub fn update<F>(&mut self, f: F) -> &InnerData
@@ -105,3 +279,85 @@ impl StorageData {
         &self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{Action, GestureKind, Trigger};
+    use crate::input::{Button, LongPress};
+
+    use super::*;
+
+    #[test]
+    fn current_layout_is_listed() {
+        assert_eq!(LAYOUTS.last(), Some(&(RECORD.version(), 12)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut data = InnerData::const_default();
+        data.position_1 = Some(Millimeters::from_mm(720));
+        data.long_press = LongPress::CHOICES[0];
+        data.locked = true;
+        data.unit = Unit::Inches;
+        data.pixel_shift = false;
+
+        let mut buffer = [0; FlashRecord::buffer_size::<InnerData>()];
+        let bytes = postcard::to_slice(&data, &mut buffer).unwrap();
+        let loaded = InnerData::from_bytes(RECORD.version(), bytes)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(loaded.position_1, data.position_1);
+        assert_eq!(loaded.long_press, LongPress::CHOICES[0]);
+        assert!(loaded.locked);
+        assert_eq!(loaded.unit, Unit::Inches);
+        assert!(!loaded.pixel_shift);
+    }
+
+    #[test]
+    fn migrates_older_layouts() {
+        let mut calibration = Calibration::new();
+        calibration.insert(1000, Millimeters::from_mm(650)).unwrap();
+        calibration
+            .insert(3000, Millimeters::from_mm(1250))
+            .unwrap();
+        let mut actions = ActionMap::const_default();
+        let trigger = Trigger {
+            buttons: Button::Pos2,
+            gesture: GestureKind::DoublePress,
+        };
+        actions.set(trigger, Action::ToggleSitStand).unwrap();
+        // layout of version 56, i.e. up to the child lock
+        let old = (
+            Some(Millimeters::from_mm(700)),
+            Some(Millimeters::from_mm(1100)),
+            calibration,
+            LongPress::CHOICES[0],
+            actions,
+            true,
+        );
+
+        let mut buffer = [0; FlashRecord::buffer_size::<InnerData>()];
+        let bytes = postcard::to_slice(&old, &mut buffer).unwrap();
+        let loaded = InnerData::from_bytes(56, bytes).unwrap().unwrap();
+
+        let defaults = InnerData::const_default();
+        assert_eq!(loaded.position_1, old.0);
+        assert_eq!(loaded.position_2, old.1);
+        assert_eq!(loaded.calibration.as_slice(), old.2.as_slice());
+        assert_eq!(loaded.long_press, old.3);
+        assert_eq!(loaded.actions.get(trigger), Action::ToggleSitStand);
+        assert!(loaded.locked);
+        assert_eq!(loaded.reminder, defaults.reminder);
+        assert_eq!(loaded.collision_sensitivity, defaults.collision_sensitivity);
+        assert_eq!(loaded.unit, defaults.unit);
+        assert_eq!(loaded.display_timeout, defaults.display_timeout);
+        assert!(loaded.pixel_shift);
+    }
+
+    #[test]
+    fn rejects_unknown_layouts() {
+        assert!(InnerData::from_bytes(52, &[0; 16]).is_none());
+        assert!(InnerData::from_bytes(RECORD.version() + 1, &[0; 16]).is_none());
+    }
+}
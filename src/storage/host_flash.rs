@@ -0,0 +1,51 @@
+//! Flash replacement for running the tests on the host. It behaves like erased flash that
+//! is shared by all [`FlashStorage`] instances, like the real one.
+
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::CriticalSectionMutex;
+use embedded_storage::{ReadStorage, Storage};
+
+const CAPACITY: usize = 0x10000;
+
+static FLASH: CriticalSectionMutex<RefCell<[u8; CAPACITY]>> =
+    CriticalSectionMutex::new(RefCell::new([0xFF; CAPACITY]));
+
+#[derive(Debug)]
+pub struct OutOfBounds;
+
+pub struct FlashStorage;
+
+impl FlashStorage {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn range(offset: u32, length: usize) -> Result<core::ops::Range<usize>, OutOfBounds> {
+    let start = offset as usize;
+    let end = start.checked_add(length).ok_or(OutOfBounds)?;
+    (end <= CAPACITY).then_some(start..end).ok_or(OutOfBounds)
+}
+
+impl ReadStorage for FlashStorage {
+    type Error = OutOfBounds;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        let range = range(offset, bytes.len())?;
+        FLASH.lock(|flash| bytes.copy_from_slice(&flash.borrow()[range]));
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        CAPACITY
+    }
+}
+
+impl Storage for FlashStorage {
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        let range = range(offset, bytes.len())?;
+        FLASH.lock(|flash| flash.borrow_mut()[range].copy_from_slice(bytes));
+        Ok(())
+    }
+}
@@ -20,3 +20,23 @@ macro_rules! format {
         res
     }}
 }
+
+/// Like [`format`] but cuts the output off instead of panicking if it exceeds `N` bytes.
+pub fn format_truncated<const N: usize>(args: core::fmt::Arguments) -> heapless::String<N> {
+    struct Truncating<'a, const N: usize>(&'a mut heapless::String<N>);
+
+    impl<const N: usize> Write for Truncating<'_, N> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            for c in s.chars() {
+                if self.0.push(c).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut output = heapless::String::new();
+    let _ = Truncating(&mut output).write_fmt(args);
+    output
+}
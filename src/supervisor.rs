@@ -0,0 +1,126 @@
+use embassy_time::{Duration, Instant, Timer};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Signal, DIRECTION, FAULTS};
+
+/// A task failing more often than this within [`FAILURE_WINDOW`] resets the chip.
+const MAX_FAILURES: u8 = 5;
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+/// Time a failed task waits before it restarts.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Tasks that report their failures to the supervisor instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Task {
+    Measure,
+    Display,
+    OperationMode,
+}
+
+impl Task {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        match self {
+            Task::Measure => 0,
+            Task::Display => 1,
+            Task::OperationMode => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Fault {
+    pub task: Task,
+    pub error: &'static str,
+}
+
+/// Lets a failed task restart once the supervisor handled its fault.
+static RESTART: [Signal<()>; Task::COUNT] = [Signal::new(), Signal::new(), Signal::new()];
+
+/// Hands the failure of `task` to the supervisor and waits until the task may restart.
+pub async fn report(task: Task, error: &'static str) {
+    log::error!("{task:?} task failed: {error}");
+    let restart = &RESTART[task.index()];
+    restart.reset();
+    FAULTS.send(Fault { task, error }).await;
+    restart.wait().await;
+}
+
+/// Clears the fault after [`RETRY_DELAY`] and restarts the task that failed.
+pub async fn recover(fault: Fault) {
+    Timer::after(RETRY_DELAY).await;
+    DIRECTION.clear_fault().await;
+    RESTART[fault.task.index()].signal(());
+}
+
+/// Counts the failures of each task to decide when restarting the task does not help anymore.
+#[derive(Debug, Clone)]
+pub struct Supervisor {
+    /// Number of failures since the start of the window.
+    failures: [(u8, Instant); Task::COUNT],
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self {
+            failures: [(0, Instant::MIN); Task::COUNT],
+        }
+    }
+
+    /// Registers the failure of `task`, returns `true` if the chip should be reset.
+    pub fn register(&mut self, task: Task, now: Instant) -> bool {
+        let (count, window_start) = &mut self.failures[task.index()];
+        if now - *window_start > FAILURE_WINDOW {
+            *count = 0;
+            *window_start = now;
+        }
+        *count += 1;
+        *count > MAX_FAILURES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    #[test]
+    fn resets_after_too_many_failures_in_window() {
+        let mut supervisor = Supervisor::new();
+        for i in 0..MAX_FAILURES {
+            assert!(!supervisor.register(Task::Measure, at(u64::from(i) * 1000)));
+        }
+        assert!(supervisor.register(Task::Measure, at(10_000)));
+    }
+
+    #[test]
+    fn window_restarts_after_it_expired() {
+        let mut supervisor = Supervisor::new();
+        for _ in 0..MAX_FAILURES {
+            assert!(!supervisor.register(Task::Display, at(1000)));
+        }
+        let expired = at(1000) + FAILURE_WINDOW + Duration::from_millis(1);
+        assert!(!supervisor.register(Task::Display, expired));
+        // the new window starts with the first failure after the expired one
+        for i in 1..MAX_FAILURES {
+            let now = expired + Duration::from_secs(u64::from(i));
+            assert!(!supervisor.register(Task::Display, now));
+        }
+        assert!(supervisor.register(Task::Display, expired + FAILURE_WINDOW));
+    }
+
+    #[test]
+    fn tasks_are_counted_separately() {
+        let mut supervisor = Supervisor::new();
+        for _ in 0..MAX_FAILURES {
+            assert!(!supervisor.register(Task::Measure, at(0)));
+            assert!(!supervisor.register(Task::Display, at(0)));
+        }
+        assert!(!supervisor.register(Task::OperationMode, at(0)));
+        assert!(supervisor.register(Task::Measure, at(0)));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::Millimeters;
+
+/// Unit heights are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unit {
+    Centimeters,
+    /// Centimeters with one decimal.
+    DecimalCentimeters,
+    Millimeters,
+    /// Inches with one decimal.
+    Inches,
+}
+
+impl Unit {
+    pub const CHOICES: [Unit; 4] = [
+        Unit::Centimeters,
+        Unit::DecimalCentimeters,
+        Unit::Millimeters,
+        Unit::Inches,
+    ];
+
+    /// Formats `height` in this unit, rounded to the nearest displayable value.
+    pub fn show(self, height: Millimeters) -> Length {
+        Length {
+            mm: height.as_mm(),
+            unit: self,
+            precise: false,
+        }
+    }
+
+    /// Like [`Self::show`], but with enough decimals to show every millimeter.
+    pub fn show_precise(self, height: Millimeters) -> Length {
+        Length {
+            mm: height.as_mm(),
+            unit: self,
+            precise: true,
+        }
+    }
+}
+
+impl core::fmt::Display for Unit {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let string = match self {
+            Unit::Centimeters => "cm",
+            Unit::DecimalCentimeters => "cm with decimal",
+            Unit::Millimeters => "mm",
+            Unit::Inches => "inch",
+        };
+
+        f.write_str(string)
+    }
+}
+
+/// A length with its unit, see [`Unit::show`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Length {
+    mm: u16,
+    unit: Unit,
+    precise: bool,
+}
+
+impl core::fmt::Display for Length {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mm = u32::from(self.mm);
+        match (self.unit, self.precise) {
+            (Unit::Centimeters, false) => write!(f, "{}cm", (mm + 5) / 10),
+            (Unit::Centimeters | Unit::DecimalCentimeters, _) => {
+                write!(f, "{}.{}cm", mm / 10, mm % 10)
+            }
+            (Unit::Millimeters, _) => write!(f, "{mm}mm"),
+            (Unit::Inches, false) => {
+                // 1in = 25.4mm, i.e. 0.1in = 2.54mm
+                let tenths = (mm * 100 + 127) / 254;
+                write!(f, "{}.{}in", tenths / 10, tenths % 10)
+            }
+            (Unit::Inches, true) => {
+                let hundredths = (mm * 1000 + 127) / 254;
+                write!(f, "{}.{:02}in", hundredths / 100, hundredths % 100)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use heapless::String;
+
+    use super::*;
+
+    fn show(unit: Unit, mm: u16) -> String<16> {
+        let mut string = String::new();
+        write!(string, "{}", unit.show(Millimeters::from_mm(mm))).unwrap();
+        string
+    }
+
+    fn show_precise(unit: Unit, mm: u16) -> String<16> {
+        let mut string = String::new();
+        write!(string, "{}", unit.show_precise(Millimeters::from_mm(mm))).unwrap();
+        string
+    }
+
+    #[test]
+    fn centimeters_round_half_up() {
+        assert_eq!(show(Unit::Centimeters, 704), "70cm");
+        assert_eq!(show(Unit::Centimeters, 705), "71cm");
+        assert_eq!(show(Unit::Centimeters, 714), "71cm");
+        assert_eq!(show(Unit::Centimeters, 4), "0cm");
+        assert_eq!(show(Unit::Centimeters, 5), "1cm");
+    }
+
+    #[test]
+    fn decimal_centimeters_are_exact() {
+        assert_eq!(show(Unit::DecimalCentimeters, 705), "70.5cm");
+        assert_eq!(show(Unit::DecimalCentimeters, 1200), "120.0cm");
+        assert_eq!(show_precise(Unit::Centimeters, 709), "70.9cm");
+        assert_eq!(show(Unit::Millimeters, 709), "709mm");
+    }
+
+    #[test]
+    fn inches_round_to_nearest() {
+        assert_eq!(show(Unit::Inches, 127), "5.0in");
+        // 5.039in and 5.079in lie on either side of 5.05in
+        assert_eq!(show(Unit::Inches, 128), "5.0in");
+        assert_eq!(show(Unit::Inches, 129), "5.1in");
+        // 27.95in is 709.93mm
+        assert_eq!(show(Unit::Inches, 709), "27.9in");
+        assert_eq!(show(Unit::Inches, 710), "28.0in");
+        assert_eq!(show(Unit::Inches, 1), "0.0in");
+        assert_eq!(show(Unit::Inches, 2), "0.1in");
+    }
+
+    #[test]
+    fn precise_inches_round_to_nearest() {
+        assert_eq!(show_precise(Unit::Inches, 254), "10.00in");
+        assert_eq!(show_precise(Unit::Inches, 1), "0.04in");
+        // 709mm are 27.913in, 710mm are 27.953in
+        assert_eq!(show_precise(Unit::Inches, 709), "27.91in");
+        assert_eq!(show_precise(Unit::Inches, 710), "27.95in");
+    }
+}